@@ -0,0 +1,114 @@
+"""生成拼音排序数据文件`pinyin.txt`
+
+数据来源（均可离线获得）：
+- glibc的`iso14651_t1_pinyin`：按汉语拼音（含声调）排列的汉字，
+  同一读音内部按码位升序，因此码位下降处即为读音分组的边界
+  该文件没有读音标注，相邻两个读音恰好码位连续上升时会被合为一组，
+  因此再用`ANCHORS`中已知读音的单音字校验：同一组内出现不同读音的
+  锚字时，从后一个锚字处拆开
+- Perl的`Unicode::Collate::CJK::Stroke`：按笔画数分组的汉字（来自CLDR）
+
+输出格式：每行一个读音分组，行内每个汉字后紧跟其笔画数，以空格分隔
+例如`吖6 腌12 阿7`，笔画数未知时记为0
+
+用法：
+    python3 gen_pinyin.py > pinyin.txt
+"""
+
+import re
+import sys
+
+PINYIN = "/usr/share/i18n/locales/iso14651_t1_pinyin"
+STROKE = "/usr/share/perl/5.36.0/Unicode/Collate/CJK/Stroke.pm"
+
+# 已知读音的单音字，用来校验分组边界，每个读音取一个常用字
+ANCHORS = {
+    "锕": "ā", "嗄": "á", "啊": "à",
+    "哀": "āi", "癌": "ái", "矮": "ǎi", "爱": "ài",
+    "安": "ān", "俺": "ǎn", "暗": "àn",
+    "肮": "āng", "昂": "áng", "盎": "àng",
+    "嗷": "áo", "袄": "ǎo", "傲": "ào",
+    "八": "bā", "拔": "bá", "靶": "bǎ", "霸": "bà",
+    "掰": "bāi", "白": "bái", "摆": "bǎi", "败": "bài",
+    "班": "bān", "板": "bǎn", "半": "bàn",
+    "包": "bāo", "雹": "báo", "宝": "bǎo", "抱": "bào",
+    "妈": "mā", "麻": "má", "马": "mǎ", "骂": "mà",
+    "霾": "mái", "买": "mǎi", "卖": "mài",
+    "摸": "mō", "魔": "mó", "墨": "mò",
+    "七": "qī", "齐": "qí", "起": "qǐ", "气": "qì",
+    "诗": "shī", "时": "shí", "史": "shǐ", "是": "shì",
+    "书": "shū", "赎": "shú", "鼠": "shǔ", "树": "shù",
+    "天": "tiān", "田": "tián", "舔": "tiǎn", "掭": "tiàn",
+    "温": "wēn", "文": "wén", "稳": "wěn", "问": "wèn",
+    "医": "yī", "疑": "yí", "乙": "yǐ", "意": "yì",
+    "蒿": "hāo", "毫": "háo", "耗": "hào",
+    "肿": "zhǒng", "众": "zhòng",
+}
+
+
+def load_pinyin(path: str) -> list[list[int]]:
+    groups: list[list[int]] = []
+    last = None
+    with open(path, encoding="utf-8") as f:
+        for line in f:
+            m = re.match(r"^<U([0-9A-F]+)> ", line)
+            if m is None:
+                continue
+            cp = int(m.group(1), 16)
+            if last is None or cp < last:
+                groups.append([])
+            groups[-1].append(cp)
+            last = cp
+    return groups
+
+
+def split_anchors(groups: list[list[int]]) -> list[list[int]]:
+    result: list[list[int]] = []
+    for group in groups:
+        reading = None
+        start = 0
+        for i, cp in enumerate(group):
+            anchor = ANCHORS.get(chr(cp))
+            if anchor is None:
+                continue
+            if reading is not None and anchor != reading:
+                print(f"split {chr(cp)}({anchor}) from {reading}", file=sys.stderr)
+                result.append(group[start:i])
+                start = i
+            reading = anchor
+        result.append(group[start:])
+    return result
+
+
+def load_strokes(path: str) -> dict[int, int]:
+    strokes: dict[int, int] = {}
+    count = 0
+    data = False
+    with open(path, encoding="utf-8") as f:
+        for line in f:
+            if line.startswith("__DATA__"):
+                data = True
+                continue
+            if line.startswith("__END__"):
+                break
+            if not data:
+                continue
+            for token in line.split():
+                if token.startswith("FDD0-"):
+                    count = int(token[5:], 16) - 0x2800
+                else:
+                    strokes.setdefault(int(token, 16), count)
+    return strokes
+
+
+def main():
+    groups = split_anchors(load_pinyin(PINYIN))
+    strokes = load_strokes(STROKE)
+    out = sys.stdout
+    for group in groups:
+        out.write(" ".join(f"{chr(cp)}{strokes.get(cp, 0)}" for cp in group))
+        out.write("\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,770 @@
+//! 排序规则模块
+//!
+//! 提供排序规则特征[`Collator`]及其实现，用于决定移位序列的排列顺序
+//! - [`Magic`]：默认规则，按"a < A < b < B ..."排序
+//! - [`Bytewise`]：按字节序排序
+//! - [`CaseInsensitive`]：忽略大小写排序
+//! - [`Alphabet`]：按自定义字母表排序
+//! - [`Unicode`]：基于Unicode排序算法(UCA)的多语言排序，可以忽略大小写/重音
+//! - [`Pinyin`]：汉字按汉语拼音排序，读音相同时按笔画数排序
+//! - [`Natural`]：自然排序，连续数字按数值比较，其余部分使用内部排序规则
+//!
+//! 也可以通过字符串描述[`from_spec`]来选择排序规则，供Python和命令行使用
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::OnceLock;
+
+use feruca::{Locale, Tailoring};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::error::{Error, Result};
+use crate::index::Shift;
+use crate::magic::{magic, magic_parts};
+
+/// 排序规则特征
+///
+/// 实现者需要给出一个全序，用于[`slice::sort_unstable_by`]
+pub trait Collator: Debug + Send + Sync {
+    /// 比较两个移位序列
+    fn compare(&self, x: &str, y: &str) -> Ordering;
+
+    /// 比较两个未拼接的移位序列，结果须与拼接后调用[`Collator::compare`]一致
+    ///
+    /// 默认实现将两者拼接到线程局部的缓冲区中再比较，不会为每次比较分配内存
+    /// 可以逐片段比较的排序规则应当重写本方法
+    fn compare_shift(&self, x: Shift, y: Shift) -> Ordering {
+        thread_local! {
+            static BUF: RefCell<(String, String)> = const { RefCell::new((String::new(), String::new())) };
+        }
+
+        BUF.with(|buf| match buf.try_borrow_mut() {
+            Ok(mut buf) => {
+                let (bx, by) = &mut *buf;
+                bx.clear();
+                by.clear();
+                x.push_to(bx);
+                y.push_to(by);
+                self.compare(bx, by)
+            }
+            // 内部排序规则重入时退回到分配新的字符串
+            Err(_) => self.compare(&x.to_string(), &y.to_string()),
+        })
+    }
+
+    /// 是否支持前缀查找，即以同一前缀开头的移位序列是否连续排列，默认支持
+    ///
+    /// 不支持时[`Collator::compare_prefix`]不会被调用
+    fn supports_prefix(&self) -> bool {
+        true
+    }
+
+    /// 比较移位序列与前缀`prefix`，[`Ordering::Equal`]表示移位序列以`prefix`开头
+    ///
+    /// 用于在排序后的移位序列中二分查找前缀，因此以同一前缀开头的移位序列必须连续排列
+    /// 默认实现截取与`prefix`字符数相同的开头部分再调用[`Collator::compare`]，
+    /// 适用于逐字符比较的排序规则；无法保证连续的排序规则应当重写[`Collator::supports_prefix`]
+    fn compare_prefix(&self, shift: Shift, prefix: &str) -> Ordering {
+        let head: String = shift.chars().take(prefix.chars().count()).collect();
+        self.compare(&head, prefix)
+    }
+}
+
+/// 默认排序规则，按"a < A < b < B ..."排序
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Magic;
+
+impl Collator for Magic {
+    #[inline]
+    fn compare(&self, x: &str, y: &str) -> Ordering {
+        magic(x, y)
+    }
+
+    #[inline]
+    fn compare_shift(&self, x: Shift, y: Shift) -> Ordering {
+        let (px, py) = (x.parts().map(str::as_bytes), y.parts().map(str::as_bytes));
+        magic_parts(&px, &py)
+    }
+}
+
+/// 按字节序排序，即"A < B < a < b"
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Bytewise;
+
+impl Collator for Bytewise {
+    #[inline]
+    fn compare(&self, x: &str, y: &str) -> Ordering {
+        x.cmp(y)
+    }
+
+    #[inline]
+    fn compare_shift(&self, x: Shift, y: Shift) -> Ordering {
+        x.bytes().cmp(y.bytes())
+    }
+}
+
+/// 忽略大小写排序
+/// 忽略大小写后相同的序列再按字节序排序，保证结果稳定
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CaseInsensitive;
+
+impl Collator for CaseInsensitive {
+    fn compare(&self, x: &str, y: &str) -> Ordering {
+        let (fx, fy) = (
+            x.chars().flat_map(char::to_lowercase),
+            y.chars().flat_map(char::to_lowercase),
+        );
+
+        fx.cmp(fy).then_with(|| x.cmp(y))
+    }
+
+    fn compare_shift(&self, x: Shift, y: Shift) -> Ordering {
+        let (fx, fy) = (
+            x.chars().flat_map(char::to_lowercase),
+            y.chars().flat_map(char::to_lowercase),
+        );
+
+        fx.cmp(fy).then_with(|| x.bytes().cmp(y.bytes()))
+    }
+
+    /// 忽略大小写比较前缀，不同大小写的前缀均视为相同
+    fn compare_prefix(&self, shift: Shift, prefix: &str) -> Ordering {
+        let fp: Vec<char> = prefix.chars().flat_map(char::to_lowercase).collect();
+        let fx = shift.chars().flat_map(char::to_lowercase).take(fp.len());
+
+        fx.cmp(fp)
+    }
+}
+
+/// 按自定义字母表排序
+///
+/// 字母表中的字符按出现顺序排列，未出现的字符排在其后，按码位排序
+///
+/// # Example:
+///
+/// ```rust
+/// use rshifter::collate::{Alphabet, Collator};
+/// use std::cmp::Ordering;
+///
+/// let alphabet = Alphabet::new("bBaA");
+/// assert_eq!(alphabet.compare("b", "a"), Ordering::Less);
+/// assert_eq!(alphabet.compare("a", "z"), Ordering::Less);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Alphabet {
+    rank: HashMap<char, usize>,
+}
+
+impl Alphabet {
+    /// 由字母表字符串创建，重复出现的字符以第一次为准
+    pub fn new(alphabet: &str) -> Self {
+        let mut rank = HashMap::new();
+        for (index, ch) in alphabet.chars().enumerate() {
+            rank.entry(ch).or_insert(index);
+        }
+        Alphabet { rank }
+    }
+
+    #[inline]
+    fn key(&self, ch: char) -> (bool, usize) {
+        match self.rank.get(&ch) {
+            Some(&index) => (false, index),
+            None => (true, ch as usize),
+        }
+    }
+}
+
+impl Collator for Alphabet {
+    fn compare(&self, x: &str, y: &str) -> Ordering {
+        x.chars()
+            .map(|ch| self.key(ch))
+            .cmp(y.chars().map(|ch| self.key(ch)))
+    }
+
+    fn compare_shift(&self, x: Shift, y: Shift) -> Ordering {
+        x.chars()
+            .map(|ch| self.key(ch))
+            .cmp(y.chars().map(|ch| self.key(ch)))
+    }
+}
+
+thread_local! {
+    /// UCA比较器需要可变的缓冲区，每个线程各持有一个
+    static UCA: RefCell<feruca::Collator> =
+        RefCell::new(feruca::Collator::new(Tailoring::Cldr(Locale::Root), false, false));
+}
+
+/// 基于Unicode排序算法(UCA)的排序规则，使用CLDR根排序表
+///
+/// 按码位比较，正确处理重音字母、西里尔字母、汉字等，不会比较UTF-8的单个字节
+/// 空格与标点不会被忽略，保证单词边界参与排序
+///
+/// 忽略大小写/重音后相同的序列再按字节序排序，保证结果稳定
+///
+/// # Example:
+///
+/// ```rust
+/// use rshifter::collate::{Collator, Unicode};
+/// use std::cmp::Ordering;
+///
+/// let uca = Unicode::new();
+/// assert_eq!(uca.compare("Émile", "Ernie"), Ordering::Less);
+///
+/// let uca = Unicode::new().ignore_accents(true);
+/// assert_eq!(uca.compare("résumé", "resume"), Ordering::Greater);
+/// assert_eq!(uca.compare("résumé", "resumf"), Ordering::Less);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Unicode {
+    ignore_case: bool,
+    ignore_accents: bool,
+}
+
+impl Unicode {
+    /// 创建区分大小写与重音的UCA排序规则
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置是否忽略大小写
+    pub fn ignore_case(mut self, ignore: bool) -> Self {
+        self.ignore_case = ignore;
+        self
+    }
+
+    /// 设置是否忽略重音（即分解后的组合附加符号）
+    pub fn ignore_accents(mut self, ignore: bool) -> Self {
+        self.ignore_accents = ignore;
+        self
+    }
+
+    /// 按设置折叠大小写与重音
+    fn fold<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        if !self.ignore_case && !self.ignore_accents {
+            return Cow::Borrowed(s);
+        }
+
+        let ignore_accents = self.ignore_accents;
+        let chars = s
+            .nfd()
+            .filter(|&ch| !(ignore_accents && is_combining_mark(ch)));
+
+        if self.ignore_case {
+            Cow::Owned(chars.flat_map(char::to_lowercase).collect())
+        } else {
+            Cow::Owned(chars.collect())
+        }
+    }
+}
+
+impl Collator for Unicode {
+    fn compare(&self, x: &str, y: &str) -> Ordering {
+        let (fx, fy) = (self.fold(x), self.fold(y));
+
+        UCA.with_borrow_mut(|uca| uca.collate(fx.as_ref(), fy.as_ref()))
+            .then_with(|| x.cmp(y))
+    }
+
+    /// UCA先比较整个字符串的基本权重，再比较重音/大小写，
+    /// 因此只有基本权重的前缀（即忽略大小写与重音的前缀）才能保证连续排列
+    ///
+    /// 例如`"resume"`同样会找到以`"Résumé"`开头的序列
+    fn compare_prefix(&self, shift: Shift, prefix: &str) -> Ordering {
+        let primary = Unicode::new().ignore_case(true).ignore_accents(true);
+        let fp = primary.fold(prefix);
+        let shift = shift.to_string();
+        let head: String = primary
+            .fold(&shift)
+            .chars()
+            .take(fp.chars().count())
+            .collect();
+
+        UCA.with_borrow_mut(|uca| uca.collate(head.as_str(), fp.as_ref()))
+    }
+}
+
+/// 内置的拼音数据，由`data/gen_pinyin.py`生成
+/// 每行为一个读音分组（按拼音与声调排列），行内每个汉字后紧跟其笔画数
+const PINYIN_DATA: &str = include_str!("../data/pinyin.txt");
+
+/// 汉字到（读音分组序号，笔画数）的映射，首次使用时解析
+static PINYIN: OnceLock<HashMap<char, (u16, u8)>> = OnceLock::new();
+
+fn pinyin_table() -> &'static HashMap<char, (u16, u8)> {
+    PINYIN.get_or_init(|| {
+        let mut table = HashMap::new();
+
+        for (group, line) in PINYIN_DATA.lines().enumerate() {
+            for entry in line.split(' ') {
+                let mut chars = entry.chars();
+                let Some(han) = chars.next() else {
+                    continue;
+                };
+                // 笔画数未知时记为0，排在同读音汉字的最后
+                let strokes = match chars.as_str().parse() {
+                    Ok(0) | Err(_) => u8::MAX,
+                    Ok(n) => n,
+                };
+                table.entry(han).or_insert((group as u16, strokes));
+            }
+        }
+
+        table
+    })
+}
+
+/// 按汉语拼音排序的排序规则，拼音数据已内置，无需联网
+///
+/// 汉字先按读音（拼音与声调）排序，读音相同时按笔画数排序，最后按码位排序
+/// 非汉字字符排在汉字之前，忽略大小写比较，忽略大小写后相同的序列再按字节序排序
+///
+/// # Example:
+///
+/// ```rust
+/// use rshifter::collate::{Collator, Pinyin};
+/// use std::cmp::Ordering;
+///
+/// // bei < jing
+/// assert_eq!(Pinyin.compare("北京", "京"), Ordering::Less);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Pinyin;
+
+impl Pinyin {
+    /// 比较用的主键，非汉字字符取小写，大小写只在主键相同时由码位决定
+    #[inline]
+    fn key(table: &HashMap<char, (u16, u8)>, ch: char) -> (bool, u32, u32) {
+        match table.get(&ch) {
+            Some(&(group, strokes)) => (true, (group as u32) << 8 | strokes as u32, ch as u32),
+            None => (false, ch.to_lowercase().next().unwrap_or(ch) as u32, 0),
+        }
+    }
+}
+
+impl Collator for Pinyin {
+    fn compare(&self, x: &str, y: &str) -> Ordering {
+        let table = pinyin_table();
+
+        x.chars()
+            .map(|ch| Self::key(table, ch))
+            .cmp(y.chars().map(|ch| Self::key(table, ch)))
+            .then_with(|| x.cmp(y))
+    }
+
+    fn compare_shift(&self, x: Shift, y: Shift) -> Ordering {
+        let table = pinyin_table();
+
+        x.chars()
+            .map(|ch| Self::key(table, ch))
+            .cmp(y.chars().map(|ch| Self::key(table, ch)))
+            .then_with(|| x.bytes().cmp(y.bytes()))
+    }
+
+    /// 只比较主键，非汉字前缀忽略大小写
+    fn compare_prefix(&self, shift: Shift, prefix: &str) -> Ordering {
+        let table = pinyin_table();
+        let fp: Vec<_> = prefix.chars().map(|ch| Self::key(table, ch)).collect();
+        let fx = shift.chars().map(|ch| Self::key(table, ch)).take(fp.len());
+
+        fx.cmp(fp)
+    }
+}
+
+/// 自然排序规则，如"Chapter 2 < Chapter 10"
+///
+/// 将字符串切分为连续数字与非数字片段，数字片段按数值比较，
+/// 非数字片段使用内部排序规则`inner`比较，从而与已有的字母顺序组合使用
+/// 数值相同的序列（如"01"与"1"）最后按内部排序规则比较整个字符串
+///
+/// # Example:
+///
+/// ```rust
+/// use rshifter::collate::{Collator, Magic, Natural};
+/// use std::cmp::Ordering;
+///
+/// let natural = Natural::new(Box::new(Magic));
+/// assert_eq!(natural.compare("Chapter 2", "Chapter 10"), Ordering::Less);
+/// ```
+#[derive(Debug)]
+pub struct Natural {
+    inner: Box<dyn Collator>,
+}
+
+impl Natural {
+    /// 以`inner`比较非数字片段
+    pub fn new(inner: Box<dyn Collator>) -> Self {
+        Natural { inner }
+    }
+}
+
+/// 将字符串切分为连续ASCII数字与非数字片段
+fn segments(s: &str) -> impl Iterator<Item = &str> {
+    let mut rest = s;
+
+    std::iter::from_fn(move || {
+        let first = rest.chars().next()?;
+        let digit = first.is_ascii_digit();
+        let end = rest
+            .find(|ch: char| ch.is_ascii_digit() != digit)
+            .unwrap_or(rest.len());
+
+        let (head, tail) = rest.split_at(end);
+        rest = tail;
+        Some(head)
+    })
+}
+
+/// 按数值比较两个ASCII数字片段，不受位数限制
+fn cmp_numeric(x: &str, y: &str) -> Ordering {
+    let (tx, ty) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+
+    tx.len().cmp(&ty.len()).then_with(|| tx.cmp(ty))
+}
+
+impl Collator for Natural {
+    fn compare(&self, x: &str, y: &str) -> Ordering {
+        let is_number = |s: &str| s.starts_with(|ch: char| ch.is_ascii_digit());
+        let (mut sx, mut sy) = (segments(x), segments(y));
+
+        loop {
+            let ord = match (sx.next(), sy.next()) {
+                (Some(px), Some(py)) if is_number(px) && is_number(py) => cmp_numeric(px, py),
+                (Some(px), Some(py)) => self.inner.compare(px, py),
+                _ => break self.inner.compare(x, y),
+            };
+
+            if ord != Ordering::Equal {
+                break ord;
+            }
+        }
+    }
+
+    fn supports_prefix(&self) -> bool {
+        self.inner.supports_prefix()
+    }
+
+    /// 数字按数值比较，如"10"排在"2"之后，以"1"开头的序列不连续，
+    /// 因此`prefix`中的数字片段须与完整的数字片段数值相同：`"Chapter 1"`匹配`"Chapter 01 …"`，
+    /// 不匹配`"Chapter 10"`；最后一个非数字片段按内部排序规则比较前缀
+    fn compare_prefix(&self, shift: Shift, prefix: &str) -> Ordering {
+        let is_number = |s: &str| s.starts_with(|ch: char| ch.is_ascii_digit());
+        let shift = shift.to_string();
+        let mut sx = segments(&shift);
+        let mut sp = segments(prefix).peekable();
+
+        while let Some(py) = sp.next() {
+            let Some(px) = sx.next() else {
+                return Ordering::Less;
+            };
+
+            let ord = match (is_number(px), is_number(py)) {
+                (true, true) => cmp_numeric(px, py),
+                (false, false) if sp.peek().is_none() => {
+                    self.inner.compare_prefix(Shift::whole(px), py)
+                }
+                _ => self.inner.compare(px, py),
+            };
+
+            if ord != Ordering::Equal {
+                return ord;
+            }
+        }
+
+        Ordering::Equal
+    }
+}
+
+/// 通过字符串描述选择排序规则
+///
+/// | 描述             | 排序规则              |
+/// | ---------------- | --------------------- |
+/// | `magic`          | [`Magic`]             |
+/// | `bytes`          | [`Bytewise`]          |
+/// | `nocase`         | [`CaseInsensitive`]   |
+/// | `alphabet:aAbB…` | [`Alphabet`]          |
+/// | `unicode`        | [`Unicode`]           |
+/// | `pinyin`         | [`Pinyin`]            |
+/// | `natural[:描述]` | [`Natural`]           |
+///
+/// `natural`可以附加另一个描述作为内部排序规则，默认为`magic`，如`natural:nocase`
+///
+/// `unicode`可以附加以逗号分隔的选项`nocase`与`noaccent`，如`unicode:nocase,noaccent`
+pub fn from_spec(spec: &str) -> Result<Box<dyn Collator>> {
+    let (name, arg) = match spec.split_once(':') {
+        Some((name, arg)) => (name, Some(arg)),
+        None => (spec, None),
+    };
+
+    match (name, arg) {
+        ("magic", None) => Ok(Box::new(Magic)),
+        ("bytes", None) => Ok(Box::new(Bytewise)),
+        ("nocase", None) => Ok(Box::new(CaseInsensitive)),
+        ("alphabet", Some(alphabet)) if !alphabet.is_empty() => {
+            Ok(Box::new(Alphabet::new(alphabet)))
+        }
+        ("pinyin", None) => Ok(Box::new(Pinyin)),
+        ("natural", None) => Ok(Box::new(Natural::new(Box::new(Magic)))),
+        ("natural", Some(inner)) => Ok(Box::new(Natural::new(from_spec(inner)?))),
+        ("unicode", options) => {
+            let mut uca = Unicode::new();
+            for option in options.into_iter().flat_map(|s| s.split(',')) {
+                uca = match option.trim() {
+                    "nocase" => uca.ignore_case(true),
+                    "noaccent" => uca.ignore_accents(true),
+                    _ => return Err(Error::Collation(spec.to_string())),
+                };
+            }
+            Ok(Box::new(uca))
+        }
+        _ => Err(Error::Collation(spec.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::index::Line;
+
+    /// 不支持前缀查找的排序规则
+    #[derive(Debug)]
+    struct AlwaysEqual;
+
+    impl Collator for AlwaysEqual {
+        fn compare(&self, _x: &str, _y: &str) -> Ordering {
+            Ordering::Equal
+        }
+
+        fn supports_prefix(&self) -> bool {
+            false
+        }
+    }
+
+    fn sorted(collator: &dyn Collator, input: &[&str]) -> Vec<String> {
+        let mut res: Vec<String> = input.iter().map(|s| s.to_string()).collect();
+        res.sort_unstable_by(|x, y| collator.compare(x, y));
+        res
+    }
+
+    #[test]
+    fn test_builtin() {
+        let input = ["b", "A", "a", "B"];
+
+        assert_eq!(sorted(&Magic, &input), ["a", "A", "b", "B"]);
+        assert_eq!(sorted(&Bytewise, &input), ["A", "B", "a", "b"]);
+        assert_eq!(sorted(&CaseInsensitive, &input), ["A", "a", "B", "b"]);
+        assert_eq!(
+            sorted(&CaseInsensitive, &["Éa", "éB", "éa"]),
+            ["Éa", "éa", "éB"]
+        );
+    }
+
+    #[test]
+    fn test_alphabet() {
+        let alphabet = Alphabet::new("zZaA");
+        let input = ["a", "Z", "b", "z", "A", "za"];
+
+        assert_eq!(sorted(&alphabet, &input), ["z", "za", "Z", "a", "A", "b"]);
+    }
+
+    #[test]
+    fn test_unicode() {
+        let input = [
+            "zebra",
+            "Émile",
+            "éclair",
+            "Ernie",
+            "ёж",
+            "Яблоко",
+            "apple",
+            "Zoo",
+        ];
+
+        assert_eq!(
+            sorted(&Unicode::new(), &input),
+            [
+                "apple",
+                "éclair",
+                "Émile",
+                "Ernie",
+                "zebra",
+                "Zoo",
+                "ёж",
+                "Яблоко"
+            ]
+        );
+
+        let nocase = Unicode::new().ignore_case(true);
+        assert_eq!(sorted(&nocase, &["b", "B", "a"]), ["a", "B", "b"]);
+
+        let noaccent = Unicode::new().ignore_accents(true);
+        assert_eq!(noaccent.compare("Peña", "Pena"), Ordering::Greater);
+        assert_eq!(noaccent.compare("Peña", "Penb"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_pinyin() {
+        let input = ["上海", "北京", "广州", "安徽", "北海", "Beijing", "阿"];
+
+        assert_eq!(
+            sorted(&Pinyin, &input),
+            ["Beijing", "阿", "安徽", "北海", "北京", "广州", "上海"]
+        );
+
+        // 同为"āi"：锿(14画)排在鎄(17画)之前，尽管码位更大
+        assert_eq!(Pinyin.compare("锿", "鎄"), Ordering::Less);
+        // 不同读音：哀(āi) < 癌(ái)
+        assert_eq!(Pinyin.compare("癌", "哀"), Ordering::Greater);
+
+        // 非汉字整串忽略大小写比较，相同时才由大小写决定
+        assert_eq!(Pinyin.compare("aa", "Ab"), Ordering::Less);
+        assert_eq!(Pinyin.compare("Ab", "ab"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_pinyin_readings() {
+        // 同一音节的不同声调必须分属不同的读音分组，且按声调升序
+        let table = pinyin_table();
+        for tones in [
+            "锕嗄啊",
+            "哀癌矮爱",
+            "八拔靶霸",
+            "妈麻马骂",
+            "诗时史是",
+            "天田舔掭",
+        ] {
+            let groups: Vec<u16> = tones.chars().map(|c| table[&c].0).collect();
+            assert!(
+                groups.windows(2).all(|w| w[0] < w[1]),
+                "{tones}: {groups:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_natural() {
+        let input = [
+            "Chapter 10",
+            "chapter 3",
+            "Chapter 2",
+            "Chapter 02",
+            "Chapter",
+            "1",
+        ];
+
+        assert_eq!(
+            sorted(&Natural::new(Box::new(Magic)), &input),
+            [
+                "chapter 3",
+                "Chapter 02",
+                "Chapter 2",
+                "Chapter 10",
+                "Chapter",
+                "1"
+            ]
+        );
+        assert_eq!(
+            sorted(&Natural::new(Box::new(Bytewise)), &input),
+            [
+                "1",
+                "Chapter",
+                "Chapter 02",
+                "Chapter 2",
+                "Chapter 10",
+                "chapter 3"
+            ]
+        );
+
+        let natural = Natural::new(Box::new(Magic));
+        let big = "9".repeat(40);
+        assert_eq!(natural.compare(&big, &format!("1{big}")), Ordering::Less);
+    }
+
+    #[test]
+    fn test_compare_shift() {
+        let lines = ["b A c", "B a", "a b", "中 文 b", "ab A", "a 10 b 9"];
+        let lines: Vec<Line> = lines.iter().map(|s| Line::new(s, None)).collect();
+        let shifts: Vec<Shift> = lines
+            .iter()
+            .flat_map(|line| (0..line.len()).map(|offset| line.shift(offset)))
+            .collect();
+
+        let collators: Vec<Box<dyn Collator>> = vec![
+            Box::new(Magic),
+            Box::new(Bytewise),
+            Box::new(CaseInsensitive),
+            Box::new(Alphabet::new("bBaA")),
+            Box::new(Unicode::new()),
+            Box::new(Pinyin),
+            Box::new(Natural::new(Box::new(Magic))),
+        ];
+
+        for collator in &collators {
+            for &x in &shifts {
+                for &y in &shifts {
+                    let expect = collator.compare(&x.to_string(), &y.to_string());
+                    assert_eq!(
+                        collator.compare_shift(x, y),
+                        expect,
+                        "{collator:?} {x} | {y}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_compare_prefix() {
+        let line = Line::new("Ab c", None);
+        let shift = line.shift(0);
+
+        assert_eq!(Magic.compare_prefix(shift, "Ab"), Ordering::Equal);
+        assert_eq!(Magic.compare_prefix(shift, "Ab c d"), Ordering::Greater);
+        assert_eq!(Magic.compare_prefix(shift, "ab"), Ordering::Greater);
+        assert_eq!(Bytewise.compare_prefix(shift, "ab"), Ordering::Less);
+        assert_eq!(CaseInsensitive.compare_prefix(shift, "aB"), Ordering::Equal);
+        assert_eq!(Pinyin.compare_prefix(shift, "aB"), Ordering::Equal);
+
+        let line = Line::new("Résumé 10", None);
+        let uca = Unicode::new();
+        assert_eq!(uca.compare_prefix(line.shift(0), "resu"), Ordering::Equal);
+        assert_eq!(uca.compare_prefix(line.shift(0), "rf"), Ordering::Less);
+        assert_eq!(uca.compare_prefix(line.shift(1), "1"), Ordering::Equal);
+
+        let natural = Natural::new(Box::new(Magic));
+        assert_eq!(
+            natural.compare_prefix(line.shift(1), "10 Ré"),
+            Ordering::Equal
+        );
+        assert_eq!(
+            natural.compare_prefix(line.shift(1), "1"),
+            Ordering::Greater
+        );
+        assert_eq!(
+            natural.compare_prefix(line.shift(1), "9"),
+            Ordering::Greater
+        );
+        assert_eq!(
+            natural.compare_prefix(line.shift(0), "Résumé 010"),
+            Ordering::Equal
+        );
+        assert!(!Natural::new(Box::new(AlwaysEqual)).supports_prefix());
+    }
+
+    #[test]
+    fn test_spec() {
+        assert!(from_spec("magic").is_ok());
+        assert!(from_spec("alphabet:aAbB").is_ok());
+        assert!(from_spec("alphabet:").is_err());
+        assert!(from_spec("magic:x").is_err());
+        assert!(from_spec("unknown").is_err());
+        assert!(from_spec("unicode").is_ok());
+        assert!(from_spec("unicode:nocase,noaccent").is_ok());
+        assert!(from_spec("unicode:upper").is_err());
+        assert!(from_spec("natural:alphabet:aAbB").is_ok());
+        assert!(from_spec("natural:unknown").is_err());
+    }
+}
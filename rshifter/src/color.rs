@@ -1,8 +1,11 @@
 //! 控制台色彩模块
 //!
 //! 提供颜色枚举[`Color`]和改变前景色的函数[`Color::color`]
+//! 以及高亮部分内容的函数[`Color::highlight`]
 //! 需要终端支持彩色
 
+use std::ops::Range;
+
 /// 取消所有的输出色彩/格式
 /// 在函数[`Color::color`]中使用，追加在改变颜色字符串的末尾
 pub const END: &str = "\x1b[0m";
@@ -33,9 +36,11 @@ impl Color {
     /// # Example:
     ///
     /// ```rust
+    /// use rshifter::color::Color;
+    ///
     /// let red = Color::Red;
     ///
-    /// println!("{}", Color::Geeen.color("This text will be green"));
+    /// println!("{}", Color::Green.color("This text will be green"));
     /// println!("{}", red.color("This text will be red"));
     /// ```
     pub fn color(&self, string: &str) -> String {
@@ -44,4 +49,34 @@ impl Color {
 
         format!("\x1b[{clr}m{string}{END}")
     }
+
+    /// 将`string`中字节范围`ranges`内的部分改变为自身颜色，其余部分使用`base`颜色（若有）
+    /// `ranges`须按起始位置递增、互不重叠且位于字符边界上
+    ///
+    /// # Example:
+    ///
+    /// ```rust
+    /// use rshifter::color::Color;
+    ///
+    /// let text = "Beijing Institute of Technology";
+    /// println!("{}", Color::Red.highlight(text, &[0..7], Some(&Color::Blue)));
+    /// assert_eq!(Color::Red.highlight(text, &[], None), text);
+    /// ```
+    pub fn highlight(&self, string: &str, ranges: &[Range<usize>], base: Option<&Color>) -> String {
+        let plain = |s: &str| match base {
+            Some(color) if !s.is_empty() => color.color(s),
+            _ => s.to_string(),
+        };
+
+        let mut res = String::with_capacity(string.len());
+        let mut last = 0;
+        for range in ranges {
+            res.push_str(&plain(&string[last..range.start]));
+            res.push_str(&self.color(&string[range.clone()]));
+            last = range.end;
+        }
+        res.push_str(&plain(&string[last..]));
+
+        res
+    }
 }
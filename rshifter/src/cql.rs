@@ -0,0 +1,404 @@
+//! 单词级模式模块，语法仿照语料库查询语言(CQL)
+//!
+//! 模式由若干单词条件组成，每个条件匹配一个单词：
+//! - `[word="Bei.*"]`：单词与正则表达式完全匹配，`!=`表示不匹配，引号后加`%c`忽略大小写
+//! - `"Bei.*"`：`[word="Bei.*"]`的简写
+//! - `[]`：任意单词
+//! - 方括号内可以使用`&`、`|`、`!`与括号组合条件，如`[word="a.*" & !word="ab"]`
+//! - 条件后可以加数量`?`、`*`、`+`、`{n}`、`{n,}`、`{n,m}`，如`[]{0,2}`
+//!
+//! 模式在每一行已切分的单词序列上匹配，与字符串搜索相同，匹配不能跨越原行末尾
+//!
+//! ```rust
+//! use rshifter::cql::TokenPattern;
+//!
+//! let pattern: TokenPattern = r#"[word="Bei.*"] [] [word="(?i)technology"]"#.parse().unwrap();
+//! assert_eq!(pattern.find(&["Beijing", "Institute", "Technology"]), [(0, 3)]);
+//! assert!(r#"[word="a"#.parse::<TokenPattern>().is_err());
+//! ```
+
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+use regex::{Regex, RegexBuilder};
+
+use crate::error::{Error, Result};
+
+/// 单词条件
+#[derive(Debug, Clone)]
+enum Cond {
+    /// 任意单词
+    Any,
+    /// 单词与正则表达式完全匹配
+    Word(Regex),
+    Not(Box<Cond>),
+    And(Box<Cond>, Box<Cond>),
+    Or(Box<Cond>, Box<Cond>),
+}
+
+impl Cond {
+    fn matches(&self, word: &str) -> bool {
+        match self {
+            Cond::Any => true,
+            Cond::Word(re) => re.is_match(word),
+            Cond::Not(cond) => !cond.matches(word),
+            Cond::And(a, b) => a.matches(word) && b.matches(word),
+            Cond::Or(a, b) => a.matches(word) || b.matches(word),
+        }
+    }
+}
+
+/// 带数量的单词条件，连续匹配`min`到`max`个单词
+#[derive(Debug, Clone)]
+struct Item {
+    cond: Cond,
+    min: usize,
+    max: usize,
+}
+
+/// 解析后的单词级模式
+#[derive(Debug, Clone)]
+pub struct TokenPattern {
+    items: Vec<Item>,
+}
+
+impl TokenPattern {
+    /// 解析模式，语法错误时返回[`Error::Query`]，正则表达式无效时返回[`Error::Regex`]
+    pub fn parse(pattern: &str) -> Result<Self> {
+        let mut parser = Parser {
+            pattern,
+            chars: pattern.chars().collect(),
+            pos: 0,
+        };
+
+        let mut items = Vec::new();
+        while parser.skip_whitespace().is_some() {
+            items.push(parser.item()?);
+        }
+        if items.is_empty() {
+            return Err(parser.error("empty pattern"));
+        }
+
+        Ok(TokenPattern { items })
+    }
+
+    /// 在单词序列`words`中查找所有匹配，返回（起始，结束）单词位置（左闭右开）
+    ///
+    /// 同一起始位置可能有多个不同长度的匹配，不含空匹配
+    pub fn find(&self, words: &[&str]) -> Vec<(usize, usize)> {
+        let n = words.len();
+        // 每个条件对每个单词只计算一次
+        let table: Vec<Vec<bool>> = self
+            .items
+            .iter()
+            .map(|item| words.iter().map(|word| item.cond.matches(word)).collect())
+            .collect();
+
+        let mut res = Vec::new();
+        for start in 0..n {
+            let mut positions = BTreeSet::from([start]);
+
+            for (item, hits) in self.items.iter().zip(&table) {
+                let mut next = BTreeSet::new();
+                for &p in &positions {
+                    if item.min == 0 {
+                        next.insert(p);
+                    }
+                    let mut q = p;
+                    while q < n && q - p < item.max && hits[q] {
+                        q += 1;
+                        if q - p >= item.min {
+                            next.insert(q);
+                        }
+                    }
+                }
+
+                positions = next;
+                if positions.is_empty() {
+                    break;
+                }
+            }
+
+            res.extend(
+                positions
+                    .into_iter()
+                    .filter(|&end| end > start)
+                    .map(|end| (start, end)),
+            );
+        }
+
+        res
+    }
+}
+
+impl FromStr for TokenPattern {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        TokenPattern::parse(s)
+    }
+}
+
+/// 递归下降解析，位置为字符偏移
+struct Parser<'a> {
+    pattern: &'a str,
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, msg: &str) -> Error {
+        Error::Query {
+            query: self.pattern.to_string(),
+            pos: self.pos,
+            msg: msg.to_string(),
+        }
+    }
+
+    /// 跳过空白，返回下一个字符
+    fn skip_whitespace(&mut self) -> Option<char> {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, ch: char) -> bool {
+        if self.skip_whitespace() == Some(ch) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, ch: char, msg: &str) -> Result<()> {
+        if self.eat(ch) {
+            Ok(())
+        } else {
+            Err(self.error(msg))
+        }
+    }
+
+    /// `item := ("[" or? "]" | string) quantifier?`
+    fn item(&mut self) -> Result<Item> {
+        let cond = match self.skip_whitespace() {
+            Some('[') => {
+                self.pos += 1;
+                if self.eat(']') {
+                    Cond::Any
+                } else {
+                    let cond = self.or()?;
+                    self.expect(']', "expected `]`")?;
+                    cond
+                }
+            }
+            Some('"') => self.regex()?,
+            _ => return Err(self.error("expected `[` or `\"`")),
+        };
+
+        let (min, max) = self.quantifier()?;
+        Ok(Item { cond, min, max })
+    }
+
+    /// `or := and ("|" and)*`
+    fn or(&mut self) -> Result<Cond> {
+        let mut res = self.and()?;
+        while self.eat('|') {
+            res = Cond::Or(Box::new(res), Box::new(self.and()?));
+        }
+        Ok(res)
+    }
+
+    /// `and := not ("&" not)*`
+    fn and(&mut self) -> Result<Cond> {
+        let mut res = self.not()?;
+        while self.eat('&') {
+            res = Cond::And(Box::new(res), Box::new(self.not()?));
+        }
+        Ok(res)
+    }
+
+    /// `not := "!" not | "(" or ")" | "word" ("=" | "!=") string`
+    fn not(&mut self) -> Result<Cond> {
+        if self.eat('!') {
+            return Ok(Cond::Not(Box::new(self.not()?)));
+        }
+        if self.eat('(') {
+            let cond = self.or()?;
+            self.expect(')', "expected `)`")?;
+            return Ok(cond);
+        }
+
+        self.skip_whitespace();
+        let start = self.pos;
+        while self
+            .chars
+            .get(self.pos)
+            .is_some_and(|c| c.is_ascii_alphabetic())
+        {
+            self.pos += 1;
+        }
+        let attr: String = self.chars[start..self.pos].iter().collect();
+        if attr != "word" {
+            self.pos = start;
+            return Err(self.error("expected attribute `word`"));
+        }
+
+        let negated = self.eat('!');
+        self.expect('=', "expected `=` or `!=`")?;
+        self.skip_whitespace();
+        let cond = self.regex()?;
+
+        Ok(if negated {
+            Cond::Not(Box::new(cond))
+        } else {
+            cond
+        })
+    }
+
+    /// 带引号的正则表达式及可选的`%c`，须与整个单词匹配
+    fn regex(&mut self) -> Result<Cond> {
+        let quote = self.pos;
+        self.expect('"', "expected `\"`")?;
+
+        let mut source = String::new();
+        loop {
+            match self.chars.get(self.pos) {
+                None => {
+                    self.pos = quote;
+                    return Err(self.error("unclosed quote"));
+                }
+                Some('"') => break,
+                Some('\\') if self.chars.get(self.pos + 1) == Some(&'"') => {
+                    source.push('"');
+                    self.pos += 2;
+                }
+                Some('\\') if self.chars.get(self.pos + 1).is_some() => {
+                    source.push('\\');
+                    source.push(self.chars[self.pos + 1]);
+                    self.pos += 2;
+                }
+                Some(&ch) => {
+                    source.push(ch);
+                    self.pos += 1;
+                }
+            }
+        }
+        self.pos += 1;
+
+        let ignore_case = self.chars.get(self.pos) == Some(&'%');
+        if ignore_case {
+            self.pos += 1;
+            if self.chars.get(self.pos) != Some(&'c') {
+                return Err(self.error("unknown flag, expected `%c`"));
+            }
+            self.pos += 1;
+        }
+
+        let re = RegexBuilder::new(&format!("^(?:{source})$"))
+            .case_insensitive(ignore_case)
+            .build()
+            .map_err(|err| Error::Regex { re: source, err })?;
+        Ok(Cond::Word(re))
+    }
+
+    /// `quantifier := "?" | "*" | "+" | "{" n ("," m?)? "}"`，默认为`{1}`
+    fn quantifier(&mut self) -> Result<(usize, usize)> {
+        let res = match self.chars.get(self.pos) {
+            Some('?') => (0, 1),
+            Some('*') => (0, usize::MAX),
+            Some('+') => (1, usize::MAX),
+            Some('{') => {
+                self.pos += 1;
+                let min = self.number()?;
+                let max = if self.eat(',') {
+                    if self.skip_whitespace() == Some('}') {
+                        usize::MAX
+                    } else {
+                        self.number()?
+                    }
+                } else {
+                    min
+                };
+                if self.skip_whitespace() != Some('}') {
+                    return Err(self.error("expected `}`"));
+                }
+                if max < min {
+                    return Err(self.error("invalid repetition range"));
+                }
+                (min, max)
+            }
+            _ => return Ok((1, 1)),
+        };
+
+        self.pos += 1;
+        Ok(res)
+    }
+
+    fn number(&mut self) -> Result<usize> {
+        self.skip_whitespace();
+        let start = self.pos;
+        while self.chars.get(self.pos).is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+
+        let digits: String = self.chars[start..self.pos].iter().collect();
+        digits.parse().map_err(|_| {
+            self.pos = start;
+            self.error("expected a number")
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn find(pattern: &str, text: &str) -> Vec<(usize, usize)> {
+        let words: Vec<&str> = text.split(' ').collect();
+        TokenPattern::parse(pattern).unwrap().find(&words)
+    }
+
+    fn error_pos(pattern: &str) -> usize {
+        match TokenPattern::parse(pattern) {
+            Err(Error::Query { pos, .. }) => pos,
+            res => panic!("expected error, got {res:?}"),
+        }
+    }
+
+    #[test]
+    fn test_find() {
+        let text = "Beijing Institute of Technology";
+
+        assert_eq!(
+            find(r#"[word="Bei.*"] [] [word="(?i)technology"]"#, text),
+            []
+        );
+        assert_eq!(find(r#""Bei.*" []{1,2} "technology"%c"#, text), [(0, 4)]);
+        assert_eq!(find(r#""Bei"*"#, text), []);
+        assert_eq!(find(r#"[word!="of"]"#, text), [(0, 1), (1, 2), (3, 4)]);
+        assert_eq!(
+            find(r#"[] [word="[a-z]+" | word="T.*"]"#, text),
+            [(1, 3), (2, 4)]
+        );
+        assert_eq!(find(r#"[!(word=".*e.*" & word!="of")]"#, text), [(2, 3)]);
+        assert_eq!(find(r#"[]? "of""#, text), [(1, 3), (2, 3)]);
+        assert_eq!(find(r#"[]+"#, "a b"), [(0, 1), (0, 2), (1, 2)]);
+        assert_eq!(find(r#""\w+\"?""#, "a\" b"), [(0, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert_eq!(error_pos(""), 0);
+        assert_eq!(error_pos("[word=\"a\""), 9);
+        assert_eq!(error_pos("[lemma=\"a\"]"), 1);
+        assert_eq!(error_pos("\"a\"{2,1}"), 7);
+        assert_eq!(error_pos("[word=\"a]"), 6);
+        assert_eq!(error_pos("\"a\"%x"), 4);
+        assert!(matches!(
+            TokenPattern::parse("\"(\""),
+            Err(Error::Regex { .. })
+        ));
+    }
+}
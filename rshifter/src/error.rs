@@ -0,0 +1,88 @@
+//! 错误类型模块
+//!
+//! 提供纯Rust接口使用的错误枚举[`Error`]
+//! 启用`python`特性时，会在绑定层转换为对应的Python异常
+
+use std::fmt;
+use std::io;
+
+/// 本库的[`Result`](std::result::Result)别名
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// 错误枚举
+#[derive(Debug)]
+pub enum Error {
+    /// 参数越界，携带越界的参数名
+    OutOfRange(&'static str),
+    /// 正则表达式编译失败，携带原始表达式`re`与错误`err`
+    Regex { re: String, err: regex::Error },
+    /// 无法识别的排序规则描述，参见[`crate::collate::from_spec`]
+    Collation(String),
+    /// 读取文件失败
+    Io(io::Error),
+    /// 当前排序规则不支持该操作，携带操作名
+    Unsupported(&'static str),
+    /// 线程池创建失败
+    ThreadPool(rayon::ThreadPoolBuildError),
+    /// 查询解析失败，携带原始查询`query`、出错的字符偏移`pos`与原因`msg`，参见[`crate::query`]与[`crate::cql`]
+    Query {
+        query: String,
+        pos: usize,
+        msg: String,
+    },
+    /// 多模式自动机创建失败，参见[`crate::RapidShifter::search_many`]
+    Patterns(aho_corasick::BuildError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::OutOfRange(arg) => write!(f, "arg `{arg}` is out of range"),
+            Error::Regex { re, err } => match err {
+                regex::Error::CompiledTooBig(n) => write!(f, "Arg `{re}`(size: {n}) is too big"),
+                regex::Error::Syntax(_) => {
+                    write!(f, "Arg `{re}` is not a valid Regular Expression")
+                }
+                err => write!(f, "Arg `{re}`: {err}"),
+            },
+            Error::Collation(spec) => write!(f, "unknown collation `{spec}`"),
+            Error::Unsupported(op) => write!(f, "`{op}` is not supported by the active collation"),
+            Error::Io(err) => err.fmt(f),
+            Error::ThreadPool(err) => write!(f, "failed to build thread pool: {err}"),
+            Error::Query { query, pos, msg } => {
+                write!(f, "invalid query `{query}` at position {pos}: {msg}")
+            }
+            Error::Patterns(err) => write!(f, "failed to build pattern automaton: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Regex { err, .. } => Some(err),
+            Error::Io(err) => Some(err),
+            Error::ThreadPool(err) => Some(err),
+            Error::Patterns(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<rayon::ThreadPoolBuildError> for Error {
+    fn from(err: rayon::ThreadPoolBuildError) -> Self {
+        Error::ThreadPool(err)
+    }
+}
+
+impl From<aho_corasick::BuildError> for Error {
+    fn from(err: aho_corasick::BuildError) -> Self {
+        Error::Patterns(err)
+    }
+}
@@ -0,0 +1,117 @@
+//! 模糊搜索模块
+//!
+//! 在按字节序排序的词表上计算编辑距离：相邻单词共享公共前缀的动态规划行，
+//! 等价于在词表构成的前缀树上运行编辑距离自动机；当某一行的最小值已超过允许的距离时，
+//! 跳过所有具有该前缀的单词
+//!
+//! 距离为Damerau-Levenshtein距离（最优字符串对齐），插入、删除、替换与相邻字符交换均计为一次编辑
+
+/// 词表`words`（须按字节序排序）中与`term`距离不超过`max`的单词，返回（编号，距离）
+pub(crate) fn within(words: &[String], term: &str, max: usize) -> Vec<(usize, usize)> {
+    let term: Vec<char> = term.chars().collect();
+
+    // `rows[i]`为前缀前`i`个字符与`term`各前缀之间的距离
+    let mut rows: Vec<Vec<usize>> = vec![(0..=term.len()).collect()];
+    let mut prefix: Vec<char> = Vec::new();
+    let mut res = Vec::new();
+
+    let mut i = 0;
+    while i < words.len() {
+        let word: Vec<char> = words[i].chars().collect();
+        let common = prefix.iter().zip(&word).take_while(|(a, b)| a == b).count();
+        prefix.truncate(common);
+        rows.truncate(common + 1);
+
+        let mut pruned = false;
+        for &c in &word[common..] {
+            prefix.push(c);
+            let row = next_row(&rows, &prefix, &term);
+            pruned = row.iter().all(|&d| d > max);
+            rows.push(row);
+            if pruned {
+                break;
+            }
+        }
+
+        if pruned {
+            // 具有相同前缀的单词在排序后的词表中是连续的
+            let prefix: String = prefix.iter().collect();
+            i += words[i..].partition_point(|word| word.starts_with(&prefix));
+            continue;
+        }
+
+        let distance = rows[rows.len() - 1][term.len()];
+        if distance <= max {
+            res.push((i, distance));
+        }
+        i += 1;
+    }
+
+    res
+}
+
+/// 在`rows`之后追加`prefix`最后一个字符得到的新行
+fn next_row(rows: &[Vec<usize>], prefix: &[char], term: &[char]) -> Vec<usize> {
+    let r = prefix.len();
+    let c = prefix[r - 1];
+    let prev = &rows[r - 1];
+
+    let mut row = Vec::with_capacity(term.len() + 1);
+    row.push(r);
+    for j in 1..=term.len() {
+        let cost = usize::from(term[j - 1] != c);
+        let mut d = (prev[j] + 1).min(row[j - 1] + 1).min(prev[j - 1] + cost);
+        if r > 1 && j > 1 && c == term[j - 2] && prefix[r - 2] == term[j - 1] {
+            d = d.min(rows[r - 2][j - 2] + 1);
+        }
+        row.push(d);
+    }
+
+    row
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn distance(a: &str, b: &str) -> usize {
+        let words = [a.to_string()];
+        (0..)
+            .find_map(|max| within(&words, b, max).first().map(|&(_, d)| d))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(distance("University", "Universty"), 1);
+        assert_eq!(distance("University", "Univeristy"), 1);
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("ca", "abc"), 3);
+        assert_eq!(distance("", "abc"), 3);
+        assert_eq!(distance("北京", "京北"), 1);
+    }
+
+    #[test]
+    fn test_within() {
+        let mut words: Vec<String> = ["Institute", "Instiute", "Institution", "Inst", "of", "off"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        words.sort_unstable();
+
+        let found = |term: &str, max: usize| -> Vec<(&str, usize)> {
+            within(&words, term, max)
+                .into_iter()
+                .map(|(i, d)| (words[i].as_str(), d))
+                .collect()
+        };
+
+        assert_eq!(found("Institute", 0), [("Institute", 0)]);
+        assert_eq!(
+            found("Institute", 3),
+            [("Institute", 0), ("Institution", 3), ("Instiute", 1)]
+        );
+        assert_eq!(found("of", 1), [("of", 0), ("off", 1)]);
+        assert_eq!(found("xyz", 1), []);
+    }
+}
@@ -0,0 +1,1761 @@
+//! 移位序列索引模块
+//!
+//! 每个移位序列仅存储为标记[`Mark`]，即（行号，单词偏移），不再为每个移位序列拼接字符串
+//! 排序时通过[`Collator::compare_shift`]循环遍历单词进行比较，
+//! 只有在需要展示或返回字符串时才会拼接，参见[`Shift`]
+//!
+//! [`ShiftIndex`]由[`crate::RapidShifter`]与[`crate::RapidShifterLines`]共用
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+
+use aho_corasick::AhoCorasick;
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+use crate::collate::{Collator, Magic};
+use crate::color::Color;
+use crate::cql::TokenPattern;
+use crate::error::{Error, Result};
+use crate::permuterm::Permuterm;
+use crate::query::{Field, Query};
+use crate::search::{self, Finder, FuzzyHit, Match, Needle, SearchHit, SearchOptions, Span};
+use crate::stopwords::StopWords;
+
+/// 若未匹配到URL，使用该字符串代替
+pub(crate) const NONE: &str = "<None>";
+/// 高亮匹配位置使用的颜色
+const HIGHLIGHT: Color = Color::Red;
+
+/// 一行输入
+///
+/// `text`为以单个空格连接的所有单词，`starts`为每个单词在`text`中的起始位置
+#[derive(Debug)]
+pub(crate) struct Line {
+    text: String,
+    starts: Vec<u32>,
+    url: Option<String>,
+}
+
+impl Line {
+    /// 以ASCII空白切分`desc`
+    pub(crate) fn new(desc: &str, url: Option<String>) -> Self {
+        let mut text = String::with_capacity(desc.len());
+        let mut starts = Vec::new();
+
+        for word in desc.split_ascii_whitespace() {
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            starts.push(text.len() as u32);
+            text.push_str(word);
+        }
+
+        Line { text, starts, url }
+    }
+
+    pub(crate) fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    /// 单词个数，即该行的移位序列个数
+    pub(crate) fn len(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// 第`index`个单词
+    pub(crate) fn word(&self, index: usize) -> &str {
+        let start = self.starts[index] as usize;
+        let end = self
+            .starts
+            .get(index + 1)
+            .map_or(self.text.len(), |&next| next as usize - 1);
+
+        &self.text[start..end]
+    }
+
+    /// 以第`offset`个单词开头的移位序列
+    pub(crate) fn shift(&self, offset: usize) -> Shift<'_> {
+        let start = self.starts[offset] as usize;
+
+        Shift {
+            tail: &self.text[start..],
+            head: &self.text[..start.saturating_sub(1)],
+        }
+    }
+
+    /// 以第`offset`个单词开头的移位序列中，第`index`个单词的起始位置
+    fn position(&self, offset: usize, index: usize) -> usize {
+        let (start, at) = (self.starts[offset] as usize, self.starts[index] as usize);
+        if index >= offset {
+            at - start
+        } else {
+            self.text.len() - start + 1 + at
+        }
+    }
+
+    /// 按单词偏移标记`a`与`b`相距不超过`distance`个单词的移位序列，`ordered`为真时`a`须在`b`之前
+    ///
+    /// 单词位置为原行中的位置；第i与第j个单词（i < j）出现在以第k个单词开头、
+    /// 且`k <= i`或`k > j`的移位序列中，其余移位序列在两者之间截断原行
+    pub(crate) fn find_near(&self, a: &str, b: &str, distance: usize, ordered: bool) -> Vec<bool> {
+        let n = self.len();
+        let positions =
+            |word: &str| -> Vec<usize> { (0..n).filter(|&i| self.word(i) == word).collect() };
+        let (xs, ys) = (positions(a), positions(b));
+
+        // 差分数组，`diff[k]`累计以第k个单词开头的移位序列中完整出现的配对数
+        let mut diff = vec![0i32; n + 1];
+        for &x in &xs {
+            for &y in &ys {
+                let (i, j) = (x.min(y), x.max(y));
+                if x == y || j - i > distance || (ordered && x > y) {
+                    continue;
+                }
+                diff[0] += 1;
+                diff[i + 1] -= 1;
+                diff[j + 1] += 1;
+                diff[n] -= 1;
+            }
+        }
+
+        let mut count = 0;
+        diff[..n]
+            .iter()
+            .map(|d| {
+                count += d;
+                count > 0
+            })
+            .collect()
+    }
+
+    /// 按单词偏移标记包含`pat`的移位序列，`url`不为空时在移位序列后拼接`" {url}"`再匹配
+    ///
+    /// 以第k个单词开头的移位序列恰好是"{text} {text}"中从`starts[k]`开始、长度为`text.len()`的部分，
+    /// 因此只需在双倍行中查找一次`pat`，每个出现位置`p`对应`starts[k]`位于
+    /// `[p + pat.len() - text.len(), p]`内的所有移位序列，无需拼接任何移位序列
+    ///
+    /// `cross`为假时匹配不能跨越原行末尾，只需在原行中查找：以第k个单词开头的移位序列
+    /// 在`starts[k] - 1`处（分隔空格）截断原行，`starts[k]`位于`(p, p + pat.len()]`内时该出现位置被截断
+    ///
+    /// `buf`为存放双倍行的缓冲区，可以在多行之间复用
+    pub(crate) fn find_rotations(
+        &self,
+        pat: &str,
+        url: Option<&str>,
+        cross: bool,
+        buf: &mut String,
+    ) -> Vec<bool> {
+        let (n, len, m) = (self.len(), self.text.len(), pat.len());
+
+        // 匹配完全位于" {url}"中
+        let in_url = url.is_some_and(|url| {
+            url.contains(pat)
+                || pat
+                    .strip_prefix(' ')
+                    .is_some_and(|rest| url.starts_with(rest))
+        });
+        if pat.is_empty() || in_url {
+            return vec![true; n];
+        }
+
+        buf.clear();
+        buf.push_str(&self.text);
+        buf.push(' ');
+        buf.push_str(&self.text);
+
+        // 差分数组，每个出现位置对应一段或两段连续的单词偏移
+        let mut diff = vec![0i32; n + 1];
+        if cross {
+            for p in find_all(buf, pat) {
+                let lo = (p + m).saturating_sub(len);
+                let a = self.starts.partition_point(|&s| (s as usize) < lo);
+                let b = self.starts.partition_point(|&s| (s as usize) <= p);
+                if a < b {
+                    diff[a] += 1;
+                    diff[b] -= 1;
+                }
+            }
+        } else {
+            for p in find_all(&self.text, pat) {
+                let a = self.starts.partition_point(|&s| (s as usize) <= p);
+                let b = self.starts.partition_point(|&s| (s as usize) <= p + m);
+                diff[0] += 1;
+                diff[a] -= 1;
+                diff[b] += 1;
+                diff[n] -= 1;
+            }
+        }
+
+        let mut count = 0;
+        let mut hits: Vec<bool> = diff[..n]
+            .iter()
+            .map(|d| {
+                count += d;
+                count > 0
+            })
+            .collect();
+
+        // 匹配跨越移位序列末尾与" {url}"：`pat[..i]`须恰好在移位序列末尾出现
+        let Some(url) = url else {
+            return hits;
+        };
+        for i in (1..m.min(len + 1)).filter(|&i| pat.is_char_boundary(i)) {
+            let (head, rest) = pat.split_at(i);
+            let joined = rest
+                .strip_prefix(' ')
+                .is_some_and(|rest| url.starts_with(rest));
+            if !joined {
+                continue;
+            }
+
+            for q in find_all(buf, head) {
+                let Some(start) = (q + i).checked_sub(len) else {
+                    continue;
+                };
+                // `pat[..i]`须位于原行中（开头单词为第一个单词）或双倍行的后半部分
+                if !cross && start > 0 && q <= len {
+                    continue;
+                }
+                if let Ok(k) = self.starts.binary_search(&(start as u32)) {
+                    hits[k] = true;
+                }
+            }
+        }
+
+        hits
+    }
+}
+
+/// `pat`在`haystack`中所有（可以重叠的）出现位置
+fn find_all<'a>(haystack: &'a str, pat: &'a str) -> impl Iterator<Item = usize> + 'a {
+    let step = pat.chars().next().map_or(1, char::len_utf8);
+    let mut from = 0;
+
+    std::iter::from_fn(move || {
+        let p = from + haystack.get(from..)?.find(pat)?;
+        from = p + step;
+        Some(p)
+    })
+}
+
+/// 移位序列标记：行号与开头单词的偏移
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Mark {
+    pub(crate) line: u32,
+    pub(crate) offset: u32,
+}
+
+/// 未拼接的移位序列
+///
+/// 由原行的后半部分`tail`（从开头单词到行尾）与前半部分`head`组成，
+/// 拼接结果为`"{tail} {head}"`，`head`为空时即为`tail`
+///
+/// 实现了[`fmt::Display`]，需要字符串时可以直接调用`to_string`
+#[derive(Debug, Clone, Copy)]
+pub struct Shift<'a> {
+    tail: &'a str,
+    head: &'a str,
+}
+
+impl<'a> Shift<'a> {
+    /// 开头单词到原行末尾的部分
+    pub fn tail(&self) -> &'a str {
+        self.tail
+    }
+
+    /// 原行开头到开头单词之前的部分，不含分隔空格
+    pub fn head(&self) -> &'a str {
+        self.head
+    }
+
+    /// 依次拼接即为移位序列的三个片段
+    #[inline]
+    pub fn parts(&self) -> [&'a str; 3] {
+        let sep = if self.head.is_empty() { "" } else { " " };
+        [self.tail, sep, self.head]
+    }
+
+    /// 原行末尾在拼接结果中的位置，即`tail`与`head`之间分隔空格的字节位置
+    ///
+    /// 开头单词为原行第一个单词时没有分隔，返回`None`
+    pub fn wrap(&self) -> Option<usize> {
+        (!self.head.is_empty()).then_some(self.tail.len())
+    }
+
+    /// 将单个字符串视为没有前半部分的移位序列
+    pub(crate) fn whole(s: &'a str) -> Self {
+        Shift { tail: s, head: "" }
+    }
+
+    /// 拼接后的字节长度
+    pub fn len(&self) -> usize {
+        self.parts().iter().map(|s| s.len()).sum()
+    }
+
+    /// 是否为空序列
+    pub fn is_empty(&self) -> bool {
+        self.tail.is_empty()
+    }
+
+    /// 拼接后的字符迭代器
+    pub fn chars(&self) -> impl Iterator<Item = char> + 'a {
+        let [tail, sep, head] = self.parts();
+        tail.chars().chain(sep.chars()).chain(head.chars())
+    }
+
+    /// 拼接后的字节迭代器
+    pub fn bytes(&self) -> impl Iterator<Item = u8> + 'a {
+        let [tail, sep, head] = self.parts();
+        tail.bytes().chain(sep.bytes()).chain(head.bytes())
+    }
+
+    /// 追加到`buf`末尾
+    pub fn push_to(&self, buf: &mut String) {
+        for part in self.parts() {
+            buf.push_str(part);
+        }
+    }
+}
+
+impl fmt::Display for Shift<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.parts().iter().try_for_each(|part| f.write_str(part))
+    }
+}
+
+/// 一个移位序列及其来源，由[`crate::RapidShifter::entry`]等方法返回
+///
+/// 与`get`返回的`"{shift} {url}"`不同，各字段分开存储，无需再解析字符串
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShiftEntry {
+    /// 移位序列（不含URL）
+    pub text: String,
+    /// 所在行匹配到的URL
+    pub url: Option<String>,
+    /// 所在行的序号（从0开始）
+    pub source_line: usize,
+    /// 开头单词在原行中的偏移（从0开始）
+    pub rotation_offset: usize,
+    /// 开头单词，即该移位序列的关键词
+    pub keyword: String,
+}
+
+macro_rules! lazy_check {
+    ($param: expr, $func: expr) => {
+        if $param.is_none() {
+            $func;
+        }
+    };
+}
+
+/// 移位序列索引
+///
+/// 存储所有行[`Line`]与排序后的标记[`Mark`]，通过[`lazy_check`]宏惰性排序
+///
+/// 标记的产生与排序在工作窃取线程池中并行进行，
+/// 未设置线程数时使用rayon的全局线程池
+#[derive(Debug)]
+pub(crate) struct ShiftIndex {
+    lines: Vec<Line>,
+    marks: Option<Vec<Mark>>,
+
+    collator: Box<dyn Collator>,
+    stop_words: StopWords,
+    pool: Option<Arc<ThreadPool>>,
+
+    permuterm: Option<Permuterm>,
+    marker: Option<String>,
+}
+
+impl ShiftIndex {
+    pub(crate) fn new(lines: Vec<Line>) -> Self {
+        ShiftIndex {
+            lines,
+            marks: None,
+            collator: Box::new(Magic),
+            stop_words: StopWords::new(),
+            pool: None,
+            permuterm: None,
+            marker: None,
+        }
+    }
+
+    pub(crate) fn set_collator(&mut self, collator: Box<dyn Collator>) {
+        self.collator = collator;
+        self.marks = None;
+    }
+
+    pub(crate) fn set_stop_words(&mut self, stop_words: StopWords) {
+        self.stop_words = stop_words;
+        self.marks = None;
+    }
+
+    /// 设置展示时标记原行末尾的字符串
+    pub(crate) fn set_marker(&mut self, marker: Option<String>) {
+        self.marker = marker;
+    }
+
+    /// 设置线程数，`0`表示使用全局线程池
+    pub(crate) fn set_threads(&mut self, threads: usize) -> Result<()> {
+        self.pool = match threads {
+            0 => None,
+            n => Some(Arc::new(ThreadPoolBuilder::new().num_threads(n).build()?)),
+        };
+        Ok(())
+    }
+
+    /// 当前使用的线程数
+    pub(crate) fn threads(&self) -> usize {
+        self.pool
+            .as_ref()
+            .map_or_else(rayon::current_num_threads, |pool| {
+                pool.current_num_threads()
+            })
+    }
+
+    pub(crate) fn is_processed(&self) -> bool {
+        self.marks.is_some()
+    }
+
+    /// 产生所有标记并排序，丢弃以停用词开头的移位序列
+    ///
+    /// 每个工作线程将标记写入各自的缓冲区，最后合并并进行并行排序
+    pub(crate) fn process(&mut self) {
+        let run = || {
+            let mut marks: Vec<Mark> = self
+                .lines
+                .par_iter()
+                .enumerate()
+                .fold(Vec::new, |mut buf, (line, item)| {
+                    buf.extend(
+                        (0..item.len())
+                            .filter(|&offset| !self.stop_words.contains(item.word(offset)))
+                            .map(|offset| Mark {
+                                line: line as u32,
+                                offset: offset as u32,
+                            }),
+                    );
+                    buf
+                })
+                .reduce(Vec::new, |mut x, mut y| {
+                    x.append(&mut y);
+                    x
+                });
+
+            marks.par_sort_unstable_by(|x, y| {
+                self.collator
+                    .compare_shift(self.shift_of(*x), self.shift_of(*y))
+            });
+            marks
+        };
+
+        let marks = self.install(run);
+        self.marks = Some(marks);
+    }
+
+    /// 在设置的线程池中运行`op`，未设置时使用全局线程池
+    fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        match &self.pool {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
+
+    /// 排序后的所有标记
+    pub(crate) fn marks(&mut self) -> &[Mark] {
+        lazy_check!(self.marks, self.process());
+
+        self.marks.as_ref().unwrap()
+    }
+
+    pub(crate) fn line_of(&self, mark: Mark) -> &Line {
+        &self.lines[mark.line as usize]
+    }
+
+    pub(crate) fn shift_of(&self, mark: Mark) -> Shift<'_> {
+        self.line_of(mark).shift(mark.offset as usize)
+    }
+
+    /// 第`index`个移位序列，不拼接字符串
+    pub(crate) fn shift(&mut self, index: usize) -> Option<Shift<'_>> {
+        let mark = *self.marks().get(index)?;
+        Some(self.shift_of(mark))
+    }
+
+    pub(crate) fn url_of(&self, mark: Mark) -> &str {
+        self.line_of(mark).url().unwrap_or(NONE)
+    }
+
+    /// 第`index`个移位序列，格式为`"{shift} {url}"`
+    pub(crate) fn get(&mut self, index: usize, arg: &'static str) -> Result<String> {
+        let Some(&mark) = self.marks().get(index) else {
+            return Err(Error::OutOfRange(arg));
+        };
+
+        Ok(format!("{} {}", self.shift_of(mark), self.url_of(mark)))
+    }
+
+    fn entry_of(&self, mark: Mark) -> ShiftEntry {
+        let line = self.line_of(mark);
+
+        ShiftEntry {
+            text: self.shift_of(mark).to_string(),
+            url: line.url().map(String::from),
+            source_line: mark.line as usize,
+            rotation_offset: mark.offset as usize,
+            keyword: line.word(mark.offset as usize).to_string(),
+        }
+    }
+
+    /// 第`index`个移位序列及其来源
+    pub(crate) fn entry(&mut self, index: usize) -> Result<ShiftEntry> {
+        let Some(&mark) = self.marks().get(index) else {
+            return Err(Error::OutOfRange("index"));
+        };
+
+        Ok(self.entry_of(mark))
+    }
+
+    /// 所有移位序列及其来源
+    pub(crate) fn entries(&mut self) -> Vec<ShiftEntry> {
+        lazy_check!(self.marks, self.process());
+
+        self.marks
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|&mark| self.entry_of(mark))
+            .collect()
+    }
+
+    /// 以`prefix`开头的所有移位序列在排序后的序号范围
+    ///
+    /// 通过[`Collator::compare_prefix`]二分查找，耗时为O(log n)
+    /// 自然排序时前缀中的数字按数值匹配完整的数字片段，见[`crate::collate::Natural`]
+    /// 排序规则不支持前缀查找（参见[`Collator::supports_prefix`]）时返回[`Error::Unsupported`]
+    pub(crate) fn lookup(&mut self, prefix: &str) -> Result<Range<usize>> {
+        if !self.collator.supports_prefix() {
+            return Err(Error::Unsupported("lookup"));
+        }
+
+        lazy_check!(self.marks, self.process());
+
+        let marks = self.marks.as_ref().unwrap();
+        let cmp = |mark: &Mark| self.collator.compare_prefix(self.shift_of(*mark), prefix);
+
+        let start = marks.partition_point(|mark| cmp(mark) == Ordering::Less);
+        let end = start + marks[start..].partition_point(|mark| cmp(mark) == Ordering::Equal);
+
+        Ok(start..end)
+    }
+
+    /// 以`prefix`开头的补全建议，每个补全将`prefix`延伸到当前单词末尾
+    ///
+    /// 在[`ShiftIndex::lookup`]得到的范围内统计各补全出现的次数，
+    /// 按次数从多到少排列，次数相同时按排序后的位置排列，最多返回`limit`个
+    pub(crate) fn suggest(&mut self, prefix: &str, limit: usize) -> Result<Vec<(String, usize)>> {
+        let range = self.lookup(prefix)?;
+        let marks = &self.marks.as_ref().unwrap()[range];
+        let n = prefix.chars().count();
+
+        let mut counts: HashMap<String, (usize, usize)> = HashMap::new();
+        for (order, &mark) in marks.iter().enumerate() {
+            let completion: String = self
+                .shift_of(mark)
+                .chars()
+                .enumerate()
+                .take_while(|&(i, ch)| i < n || ch != ' ')
+                .map(|(_, ch)| ch)
+                .collect();
+
+            counts.entry(completion).or_insert((0, order)).0 += 1;
+        }
+
+        let mut res: Vec<(String, (usize, usize))> = counts.into_iter().collect();
+        res.sort_unstable_by(|(_, x), (_, y)| y.0.cmp(&x.0).then(x.1.cmp(&y.1)));
+
+        Ok(res
+            .into_iter()
+            .take(limit)
+            .map(|(completion, (count, _))| (completion, count))
+            .collect())
+    }
+
+    /// 为搜索结果附加移位序列及其来源
+    pub(crate) fn matches(&mut self, hits: Vec<SearchHit>) -> Vec<Match> {
+        lazy_check!(self.marks, self.process());
+
+        let marks = self.marks.as_ref().unwrap();
+        hits.into_iter()
+            .map(|hit| Match {
+                index: hit.index,
+                entry: self.entry_of(marks[hit.index]),
+                spans: hit.spans,
+            })
+            .collect()
+    }
+
+    /// 拼接所有移位序列
+    pub(crate) fn shifts(&mut self) -> Vec<String> {
+        lazy_check!(self.marks, self.process());
+
+        self.marks
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|&mark| self.shift_of(mark).to_string())
+            .collect()
+    }
+
+    pub(crate) fn show_line(&mut self, line: usize) -> Result<()> {
+        self.show_line_spans(line, &[])
+    }
+
+    /// 展示特定列，并高亮其中的匹配位置`spans`
+    pub(crate) fn show_line_spans(&mut self, line: usize, spans: &[Span]) -> Result<()> {
+        let Some(&mark) = self.marks().get(line) else {
+            return Err(Error::OutOfRange("line"));
+        };
+
+        let [in_shift, in_url] = self.clip_spans(mark, spans)?;
+        self.print_row(line, mark, in_shift, &in_url, false);
+        Ok(())
+    }
+
+    pub(crate) fn show_all(&mut self, verbose: bool) {
+        lazy_check!(self.marks, self.process());
+
+        for (num, &mark) in self.marks.as_ref().unwrap().iter().enumerate() {
+            self.print_row(num, mark, Vec::new(), &[], verbose);
+        }
+    }
+
+    /// 展示搜索结果，高亮每个移位序列中的匹配位置
+    pub(crate) fn show_hits(&mut self, hits: &[SearchHit], verbose: bool) -> Result<()> {
+        lazy_check!(self.marks, self.process());
+
+        // 先检查全部结果，避免打印到一半时出错
+        let marks = self.marks.as_ref().unwrap();
+        let rows = hits
+            .iter()
+            .map(|hit| {
+                let &mark = marks.get(hit.index).ok_or(Error::OutOfRange("hits"))?;
+                let [in_shift, in_url] = self.clip_spans(mark, &hit.spans)?;
+                Ok((hit.index, mark, in_shift, in_url))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for (num, mark, in_shift, in_url) in rows {
+            self.print_row(num, mark, in_shift, &in_url, verbose);
+        }
+
+        Ok(())
+    }
+
+    /// 将相对于`"{shift} {url}"`的匹配位置`spans`分别截取到移位序列与URL中
+    ///
+    /// `spans`可能来自其他索引，截取后的范围不在字符边界上时返回[`Error::OutOfRange`]
+    fn clip_spans(&self, mark: Mark, spans: &[Span]) -> Result<[Vec<Range<usize>>; 2]> {
+        let shift = self.shift_of(mark).to_string();
+        let url = self.url_of(mark);
+
+        let in_shift = search::clip(spans, 0, shift.len());
+        let in_url = search::clip(spans, shift.len() + 1, url.len());
+
+        let aligned = |text: &str, ranges: &[Range<usize>]| {
+            ranges
+                .iter()
+                .all(|range| text.is_char_boundary(range.start) && text.is_char_boundary(range.end))
+        };
+        if !aligned(&shift, &in_shift) || !aligned(url, &in_url) {
+            return Err(Error::OutOfRange("spans"));
+        }
+
+        Ok([in_shift, in_url])
+    }
+
+    /// 打印一行，`num`为排序后的序号，`in_shift`与`in_url`为[`Self::clip_spans`]截取后的匹配位置
+    /// 设置了标记时在原行末尾处插入标记，如`"ddd / aaa bbb ccc"`
+    fn print_row(
+        &self,
+        num: usize,
+        mark: Mark,
+        mut in_shift: Vec<Range<usize>>,
+        in_url: &[Range<usize>],
+        verbose: bool,
+    ) {
+        let wrap = self.shift_of(mark).wrap();
+        let mut shift = self.shift_of(mark).to_string();
+        let url = self.url_of(mark);
+
+        if let (Some(marker), Some(wrap)) = (&self.marker, wrap) {
+            let insert = format!("{marker} ");
+            shift.insert_str(wrap + 1, &insert);
+            for range in &mut in_shift {
+                if range.start > wrap {
+                    range.start += insert.len();
+                }
+                if range.end > wrap {
+                    range.end += insert.len();
+                }
+            }
+        }
+
+        if verbose {
+            print!("{}", Color::Purple.color(&format!("[{:0>2}] ", num + 1)));
+            println!(
+                "{} {}",
+                HIGHLIGHT.highlight(&shift, &in_shift, Some(&Color::Blue)),
+                HIGHLIGHT.highlight(url, in_url, Some(&Color::Yellow))
+            );
+        } else {
+            println!(
+                "{} {}",
+                HIGHLIGHT.highlight(&shift, &in_shift, None),
+                HIGHLIGHT.highlight(url, in_url, None)
+            );
+        };
+    }
+
+    /// 将第`mark`个移位序列（`all`为真时追加URL）拼接到`buf`中
+    fn text_into(&self, mark: Mark, all: bool, buf: &mut String) {
+        buf.clear();
+        self.shift_of(mark).push_to(buf);
+        if all {
+            buf.push(' ');
+            buf.push_str(self.url_of(mark));
+        }
+    }
+
+    /// 拼接每个移位序列（`all`为真时追加URL），返回满足`pred`的序号
+    /// `pred`的第二个参数为原行末尾的位置，参见[`Shift::wrap`]
+    ///
+    /// 移位序列划分到线程池的各个线程中并行检查，每个线程复用各自的缓冲区，
+    /// 不会为每个移位序列分配内存
+    pub(crate) fn scan(
+        &mut self,
+        all: bool,
+        pred: impl Fn(&str, Option<usize>) -> bool + Sync,
+    ) -> Option<Vec<usize>> {
+        lazy_check!(self.marks, self.process());
+
+        let marks = self.marks.as_ref().unwrap();
+        let res: Vec<usize> = self.install(|| {
+            marks
+                .par_iter()
+                .enumerate()
+                .map_init(String::new, |buf, (index, &mark)| {
+                    self.text_into(mark, all, buf);
+                    pred(buf, self.shift_of(mark).wrap()).then_some(index)
+                })
+                .flatten()
+                .collect()
+        });
+
+        if res.is_empty() {
+            None
+        } else {
+            Some(res)
+        }
+    }
+
+    /// 同时搜索多个字符串，返回每个字符串到匹配序号的映射，不含没有匹配的字符串
+    ///
+    /// 所有字符串编译为一个Aho-Corasick自动机，只需扫描一遍移位序列；匹配不能跨越原行末尾
+    pub(crate) fn search_many<S: AsRef<str>>(
+        &mut self,
+        patterns: &[S],
+        all: bool,
+    ) -> Result<HashMap<String, Vec<usize>>> {
+        lazy_check!(self.marks, self.process());
+
+        let automaton = AhoCorasick::new(patterns.iter().map(AsRef::as_ref))?;
+        let marks = self.marks.as_ref().unwrap();
+
+        // 每个移位序列中出现的字符串编号
+        let found: Vec<(usize, Vec<usize>)> = self.install(|| {
+            marks
+                .par_iter()
+                .enumerate()
+                .map_init(String::new, |buf, (index, &mark)| {
+                    self.text_into(mark, all, buf);
+                    let wrap = self.shift_of(mark).wrap();
+
+                    let mut ids: Vec<usize> = automaton
+                        .find_overlapping_iter(buf.as_str())
+                        .filter(|m| !wrap.is_some_and(|wrap| m.start() <= wrap && wrap < m.end()))
+                        .map(|m| m.pattern().as_usize())
+                        .collect();
+                    ids.sort_unstable();
+                    ids.dedup();
+                    (index, ids)
+                })
+                .filter(|(_, ids)| !ids.is_empty())
+                .collect()
+        });
+
+        let mut indices: Vec<Vec<usize>> = vec![Vec::new(); patterns.len()];
+        for (index, ids) in found {
+            for id in ids {
+                indices[id].push(index);
+            }
+        }
+
+        let mut res: HashMap<String, Vec<usize>> = HashMap::new();
+        for (pattern, indices) in patterns.iter().zip(indices) {
+            if !indices.is_empty() {
+                res.entry(pattern.as_ref().to_string()).or_insert(indices);
+            }
+        }
+
+        Ok(res)
+    }
+
+    /// 搜索特定字符串，匹配不能跨越原行末尾
+    pub(crate) fn search(&mut self, pat: &str, all: bool) -> Option<Vec<usize>> {
+        self.rotations(pat, all, false)
+    }
+
+    /// 对每一行使用[`Line::find_rotations`]直接计算包含匹配的移位序列，
+    /// 再按排序后的顺序给出序号，不会拼接任何移位序列
+    fn rotations(&mut self, pat: &str, all: bool, cross: bool) -> Option<Vec<usize>> {
+        lazy_check!(self.marks, self.process());
+
+        let marks = self.marks.as_ref().unwrap();
+        let res: Vec<usize> = self.install(|| {
+            let hits: Vec<Vec<bool>> = self
+                .lines
+                .par_iter()
+                .map_init(String::new, |buf, line| {
+                    let url = all.then(|| line.url().unwrap_or(NONE));
+                    line.find_rotations(pat, url, cross, buf)
+                })
+                .collect();
+
+            marks
+                .par_iter()
+                .enumerate()
+                .filter(|(_, mark)| hits[mark.line as usize][mark.offset as usize])
+                .map(|(index, _)| index)
+                .collect()
+        });
+
+        if res.is_empty() {
+            None
+        } else {
+            Some(res)
+        }
+    }
+
+    /// 邻近搜索，返回原行中单词`a`与`b`相距不超过`distance`个单词、且两者之间未被截断的移位序列
+    pub(crate) fn near(
+        &mut self,
+        a: &str,
+        b: &str,
+        distance: usize,
+        ordered: bool,
+    ) -> Option<Vec<usize>> {
+        lazy_check!(self.marks, self.process());
+
+        let marks = self.marks.as_ref().unwrap();
+        let res: Vec<usize> = self.install(|| {
+            let hits: Vec<Vec<bool>> = self
+                .lines
+                .par_iter()
+                .map(|line| line.find_near(a, b, distance, ordered))
+                .collect();
+
+            marks
+                .par_iter()
+                .enumerate()
+                .filter(|(_, mark)| hits[mark.line as usize][mark.offset as usize])
+                .map(|(index, _)| index)
+                .collect()
+        });
+
+        if res.is_empty() {
+            None
+        } else {
+            Some(res)
+        }
+    }
+
+    /// 通过正则表达式搜索特定字符串
+    pub(crate) fn regex_search(&mut self, re: &str, all: bool) -> Result<Option<Vec<usize>>> {
+        self.search_with(Needle::Regex(re), all, SearchOptions::new())
+    }
+
+    /// 按选项搜索字符串或正则表达式，默认选项的字符串即为[`Self::search`]
+    ///
+    /// 其余情况需要折叠大小写与重音、检查单词边界或匹配正则表达式，因此逐个拼接移位序列后匹配
+    pub(crate) fn search_with(
+        &mut self,
+        needle: Needle<'_>,
+        all: bool,
+        options: SearchOptions,
+    ) -> Result<Option<Vec<usize>>> {
+        let finder = Finder::new(needle, options)?;
+        Ok(self.finder_search(&finder, all))
+    }
+
+    fn finder_search(&mut self, finder: &Finder, all: bool) -> Option<Vec<usize>> {
+        match finder.plain_literal() {
+            Some(pat) => self.rotations(pat, all, finder.crosses_boundary()),
+            None => self.scan(all, |s, wrap| finder.is_match(s, wrap)),
+        }
+    }
+
+    /// 通配符搜索，返回开头单词与`pattern`匹配的移位序列，`*`匹配任意个字符
+    ///
+    /// 首次调用时为所有单词建立轮排索引[`Permuterm`]，之后的查询只需二分查找，无需扫描移位序列
+    pub(crate) fn wildcard_search(&mut self, pattern: &str) -> Option<Vec<usize>> {
+        lazy_check!(self.marks, self.process());
+        lazy_check!(
+            self.permuterm,
+            self.permuterm = Some(Permuterm::new(&self.lines))
+        );
+
+        let found: HashSet<Mark> = self
+            .permuterm
+            .as_ref()
+            .unwrap()
+            .query(pattern)
+            .into_iter()
+            .collect();
+        if found.is_empty() {
+            return None;
+        }
+
+        let res: Vec<usize> = self
+            .marks
+            .as_ref()
+            .unwrap()
+            .iter()
+            .enumerate()
+            .filter(|(_, mark)| found.contains(mark))
+            .map(|(index, _)| index)
+            .collect();
+
+        if res.is_empty() {
+            None
+        } else {
+            Some(res)
+        }
+    }
+
+    /// 模糊搜索，返回包含与`term`编辑距离不超过`max_distance`的单词的移位序列
+    ///
+    /// 只在去重后的词表上计算编辑距离（参见[`crate::fuzzy`]），不会与每个移位序列比较；
+    /// 结果按距离升序排列，距离相同时按排序后的序号排列
+    pub(crate) fn fuzzy_search(&mut self, term: &str, max_distance: usize) -> Vec<FuzzyHit> {
+        lazy_check!(self.marks, self.process());
+        lazy_check!(
+            self.permuterm,
+            self.permuterm = Some(Permuterm::new(&self.lines))
+        );
+
+        let mut distances: HashMap<&str, usize> = HashMap::new();
+        let mut lines: HashSet<u32> = HashSet::new();
+        for (word, distance, marks) in self.permuterm.as_ref().unwrap().fuzzy(term, max_distance) {
+            distances.insert(word, distance);
+            lines.extend(marks.iter().map(|mark| mark.line));
+        }
+
+        // 移位序列包含所在行的所有单词，只需找出这些行的移位序列
+        let marks = self.marks.as_ref().unwrap();
+        let mut res: Vec<FuzzyHit> = self.install(|| {
+            marks
+                .par_iter()
+                .enumerate()
+                .filter(|(_, mark)| lines.contains(&mark.line))
+                .map_init(String::new, |buf, (index, &mark)| {
+                    self.text_into(mark, false, buf);
+                    let mut distance = usize::MAX;
+                    let mut ranges = Vec::new();
+                    let mut start = 0;
+                    for word in buf.split(' ') {
+                        if let Some(&d) = distances.get(word) {
+                            distance = distance.min(d);
+                            ranges.push(start..start + word.len());
+                        }
+                        start += word.len() + 1;
+                    }
+
+                    FuzzyHit {
+                        index,
+                        distance,
+                        spans: search::spans(buf, ranges.into_iter()),
+                    }
+                })
+                .collect()
+        });
+
+        res.sort_by_key(|hit| hit.distance);
+        res
+    }
+
+    /// 按查询语言搜索，参见[`crate::query`]
+    pub(crate) fn query(&mut self, query: &Query) -> Option<Vec<usize>> {
+        lazy_check!(self.marks, self.process());
+
+        let res: Vec<usize> = self
+            .install(|| self.eval(query))
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, hit)| hit)
+            .map(|(index, _)| index)
+            .collect();
+
+        if res.is_empty() {
+            None
+        } else {
+            Some(res)
+        }
+    }
+
+    /// 对排序后的每个移位序列计算是否满足`query`
+    fn eval(&self, query: &Query) -> Vec<bool> {
+        let marks = self.marks.as_ref().unwrap();
+
+        match query {
+            Query::Term { field, text } => match field {
+                Field::Text => {
+                    let hits: Vec<Vec<bool>> = self
+                        .lines
+                        .par_iter()
+                        .map_init(String::new, |buf, line| {
+                            line.find_rotations(text, None, false, buf)
+                        })
+                        .collect();
+                    marks
+                        .par_iter()
+                        .map(|mark| hits[mark.line as usize][mark.offset as usize])
+                        .collect()
+                }
+                Field::Url => marks
+                    .par_iter()
+                    .map(|&mark| {
+                        self.line_of(mark)
+                            .url()
+                            .is_some_and(|url| url.contains(text.as_str()))
+                    })
+                    .collect(),
+                Field::Keyword => marks
+                    .par_iter()
+                    .map(|&mark| self.line_of(mark).word(mark.offset as usize) == text)
+                    .collect(),
+            },
+            Query::And(a, b) => {
+                let (a, b) = rayon::join(|| self.eval(a), || self.eval(b));
+                a.into_iter().zip(b).map(|(a, b)| a && b).collect()
+            }
+            Query::Or(a, b) => {
+                let (a, b) = rayon::join(|| self.eval(a), || self.eval(b));
+                a.into_iter().zip(b).map(|(a, b)| a || b).collect()
+            }
+            Query::Not(a) => self.eval(a).into_iter().map(|a| !a).collect(),
+        }
+    }
+
+    /// 按单词级模式搜索，参见[`crate::cql`]，给出每个匹配的移位序列中的所有匹配位置
+    ///
+    /// 模式只在每一行的单词序列上匹配一次：匹配的单词范围`[s, e)`出现在以第k个单词开头、
+    /// 且`k <= s`或`k >= e`的移位序列中，其余移位序列在其中间截断原行
+    pub(crate) fn token_search(&mut self, pattern: &TokenPattern) -> Vec<SearchHit> {
+        lazy_check!(self.marks, self.process());
+
+        let marks = self.marks.as_ref().unwrap();
+        self.install(|| {
+            let found: Vec<Vec<(usize, usize)>> = self
+                .lines
+                .par_iter()
+                .map(|line| {
+                    let words: Vec<&str> = (0..line.len()).map(|i| line.word(i)).collect();
+                    pattern.find(&words)
+                })
+                .collect();
+
+            marks
+                .par_iter()
+                .enumerate()
+                .map_init(String::new, |buf, (index, &mark)| {
+                    let (line, offset) = (self.line_of(mark), mark.offset as usize);
+                    let mut ranges: Vec<Range<usize>> = found[mark.line as usize]
+                        .iter()
+                        .filter(|&&(s, e)| offset <= s || offset >= e)
+                        .map(|&(s, e)| {
+                            line.position(offset, s)
+                                ..line.position(offset, e - 1) + line.word(e - 1).len()
+                        })
+                        .collect();
+                    if ranges.is_empty() {
+                        return None;
+                    }
+
+                    // 只保留互不重叠的最左最长匹配
+                    ranges.sort_unstable_by_key(|range| (range.start, Reverse(range.end)));
+                    let mut end = 0;
+                    ranges.retain(|range| {
+                        let keep = range.start >= end;
+                        if keep {
+                            end = range.end;
+                        }
+                        keep
+                    });
+
+                    self.text_into(mark, false, buf);
+                    Some(SearchHit {
+                        index,
+                        spans: search::spans(buf, ranges.into_iter()),
+                    })
+                })
+                .flatten()
+                .collect()
+        })
+    }
+
+    /// 对序号`indices`的移位序列计算匹配位置
+    fn hits(&self, indices: &[usize], all: bool, finder: &Finder) -> Vec<SearchHit> {
+        let marks = self.marks.as_ref().unwrap();
+
+        self.install(|| {
+            indices
+                .par_iter()
+                .map_init(String::new, |buf, &index| {
+                    self.text_into(marks[index], all, buf);
+                    let wrap = self.shift_of(marks[index]).wrap();
+                    SearchHit {
+                        index,
+                        spans: search::spans(buf, finder.find(buf, wrap).into_iter()),
+                    }
+                })
+                .collect()
+        })
+    }
+
+    /// 按选项搜索，并给出每个匹配的移位序列中的所有匹配位置（相对于原移位序列）
+    pub(crate) fn search_spans(
+        &mut self,
+        needle: Needle<'_>,
+        all: bool,
+        options: SearchOptions,
+    ) -> Result<Vec<SearchHit>> {
+        let finder = Finder::new(needle, options)?;
+        let indices = self.finder_search(&finder, all).unwrap_or_default();
+        Ok(self.hits(&indices, all, &finder))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::magic::magic;
+
+    #[test]
+    fn test_line() {
+        let line = Line::new("  aaa\tbbb  ccc ", None);
+
+        assert_eq!(line.text, "aaa bbb ccc");
+        assert_eq!(line.len(), 3);
+        assert_eq!(line.word(1), "bbb");
+        assert_eq!(line.word(2), "ccc");
+
+        let shift = line.shift(1);
+        assert_eq!(shift.tail(), "bbb ccc");
+        assert_eq!(shift.head(), "aaa");
+        assert_eq!(shift.to_string(), "bbb ccc aaa");
+        assert_eq!(shift.len(), "bbb ccc aaa".len());
+        assert_eq!(line.shift(0).to_string(), "aaa bbb ccc");
+        assert_eq!(shift.chars().collect::<String>(), "bbb ccc aaa");
+    }
+
+    #[test]
+    fn test_empty_line() {
+        let mut index = ShiftIndex::new(vec![Line::new("   ", None), Line::new("a b", None)]);
+
+        assert_eq!(index.shifts(), ["a b", "b a"]);
+        assert!(index.get(2, "index").is_err());
+    }
+
+    #[test]
+    fn test_find_rotations() {
+        let line = Line::new("A B C D E F", Some("http://x.cn".into()));
+        let mut buf = String::new();
+
+        let hits = |pat: &str, url: Option<&str>, cross: bool, buf: &mut String| -> Vec<usize> {
+            let hits = line.find_rotations(pat, url, cross, buf);
+            (0..line.len()).filter(|&k| hits[k]).collect()
+        };
+
+        assert_eq!(hits("B C D", None, true, &mut buf), [0, 1, 4, 5]);
+        assert_eq!(hits("E F A B", None, true, &mut buf), [2, 3, 4]);
+        assert_eq!(hits("A B C D E F", None, true, &mut buf), [0]);
+        assert_eq!(hits("F A", None, true, &mut buf), [1, 2, 3, 4, 5]);
+        assert_eq!(
+            hits("A B C D E F A", None, true, &mut buf),
+            Vec::<usize>::new()
+        );
+        assert_eq!(hits("D http", Some("http://x.cn"), true, &mut buf), [4]);
+        assert_eq!(hits("F http", Some("http://x.cn"), true, &mut buf), [0]);
+        assert_eq!(hits("x.cn", Some("http://x.cn"), true, &mut buf).len(), 6);
+
+        // 不跨越原行末尾
+        assert_eq!(hits("B C D", None, false, &mut buf), [0, 1, 4, 5]);
+        assert_eq!(hits(" C D ", None, false, &mut buf), [0, 1, 5]);
+        assert_eq!(hits("E F A B", None, false, &mut buf), Vec::<usize>::new());
+        assert_eq!(hits("F A", None, false, &mut buf), Vec::<usize>::new());
+        assert_eq!(hits("D http", Some("http://x.cn"), false, &mut buf), [4]);
+        assert_eq!(hits("F http", Some("http://x.cn"), false, &mut buf), [0]);
+        assert_eq!(hits("F A B http", Some("http://x.cn"), true, &mut buf), [2]);
+        assert_eq!(
+            hits("F A B http", Some("http://x.cn"), false, &mut buf),
+            Vec::<usize>::new()
+        );
+    }
+
+    #[test]
+    fn test_search_like_scan() {
+        let mut seed = 0x5eedu64;
+        let mut lcg = move || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (seed >> 33) as usize
+        };
+        let words = ["a", "ab", "b", "ba", "中"];
+        let pats = [
+            "a", "a b", "b a", "ab a", "a a", "a ab b", " ", "中 a", "<N", "b <N", "中 h",
+        ];
+
+        for _ in 0..50 {
+            let lines = (0..8)
+                .map(|_| {
+                    let desc: Vec<&str> = (0..1 + lcg() % 6)
+                        .map(|_| words[lcg() % words.len()])
+                        .collect();
+                    let url = (lcg() % 2 == 0).then(|| String::from("http://x"));
+                    Line::new(&desc.join(" "), url)
+                })
+                .collect();
+            let mut index = ShiftIndex::new(lines);
+
+            for pat in pats {
+                // 所有（可以重叠的）出现位置中存在不跨越原行末尾的位置
+                let contains = |s: &str, wrap: Option<usize>, cross: bool| {
+                    s.char_indices().any(|(i, _)| {
+                        s[i..].starts_with(pat)
+                            && (cross || !wrap.is_some_and(|w| i <= w && w < i + pat.len()))
+                    })
+                };
+
+                for (all, cross) in [(false, false), (false, true), (true, false), (true, true)] {
+                    let expect = index.scan(all, |s, wrap| contains(s, wrap, cross));
+                    let options = SearchOptions::new().cross_boundary(cross);
+                    assert_eq!(
+                        index.search_with(Needle::Text(pat), all, options).unwrap(),
+                        expect,
+                        "{pat:?} {all} {cross}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_search_many() {
+        let lines = vec![
+            Line::new(
+                "Beijing Institute of Technology",
+                Some("bit.edu.cn".to_string()),
+            ),
+            Line::new("Peking University", None),
+        ];
+        let mut index = ShiftIndex::new(lines);
+
+        let patterns = [
+            "Institute",
+            "ing",
+            "Technology Beijing",
+            "edu",
+            "Tsinghua",
+            "ing",
+        ];
+        let res = index.search_many(&patterns, false).unwrap();
+        assert_eq!(res.len(), 2);
+        for (pattern, indices) in &res {
+            assert_eq!(Some(indices), index.search(pattern, false).as_ref());
+        }
+
+        let res = index.search_many(&patterns, true).unwrap();
+        assert_eq!(res["edu"], index.search("edu", true).unwrap());
+        assert!(!res.contains_key("Technology Beijing"));
+    }
+
+    #[test]
+    fn test_search_spans() {
+        let mut index = ShiftIndex::new(vec![
+            Line::new("ab c ab", None),
+            Line::new("中文 c", Some("http://c.cn".into())),
+        ]);
+
+        let hits = index
+            .search_spans(Needle::Text("ab"), false, SearchOptions::new())
+            .unwrap();
+        assert_eq!(hits.len(), 3);
+        for hit in &hits {
+            let shift = index.shift(hit.index).unwrap().to_string();
+            assert!(hit.spans.iter().all(|span| &shift[span.range()] == "ab"));
+            assert_eq!(hit.spans.len(), 2);
+        }
+
+        let hits = index
+            .search_spans(Needle::Regex(r"c\.?"), true, SearchOptions::new())
+            .unwrap();
+        let hit = hits
+            .iter()
+            .find(|hit| index.get(hit.index, "index").unwrap() == "c 中文 http://c.cn")
+            .unwrap();
+        let chars: Vec<_> = hit
+            .spans
+            .iter()
+            .map(|s| (s.char_start, s.char_end))
+            .collect();
+        assert_eq!(chars, [(0, 1), (12, 14), (14, 15)]);
+
+        assert!(index
+            .search_spans(Needle::Text("zz"), true, SearchOptions::new())
+            .unwrap()
+            .is_empty());
+        assert!(index
+            .search_spans(Needle::Regex("("), false, SearchOptions::new())
+            .is_err());
+    }
+
+    #[test]
+    fn test_show_hits() {
+        let mut index = ShiftIndex::new(vec![Line::new("中文 c", Some("http://c.cn".into()))]);
+        let hits = index
+            .search_spans(Needle::Text("文"), true, SearchOptions::new())
+            .unwrap();
+        assert!(index.show_hits(&hits, false).is_ok());
+
+        // 来自其他索引的匹配位置可能落在多字节字符内部
+        let span = Span {
+            start: 0,
+            end: 1,
+            char_start: 0,
+            char_end: 1,
+        };
+        let hit = SearchHit {
+            index: index.search("中文 c", false).unwrap()[0],
+            spans: vec![span],
+        };
+        assert!(matches!(
+            index.show_hits(&[hit], false),
+            Err(Error::OutOfRange("spans"))
+        ));
+        assert!(index.show_line_spans(1, &[span]).is_err());
+    }
+
+    #[test]
+    fn test_regex_search_wrap() {
+        let mut index = ShiftIndex::new(vec![Line::new("a c x c", None)]);
+
+        let found = index.regex_search("x.*c", false).unwrap();
+        assert_eq!(found, index.search("x c", false));
+        assert_eq!(found, Some(vec![0, 2, 3]));
+    }
+
+    #[test]
+    fn test_search_with() {
+        let mut index = ShiftIndex::new(vec![
+            Line::new("Université de Genève", None),
+            Line::new("University universe", None),
+        ]);
+        let options = SearchOptions::new();
+
+        assert_eq!(
+            index
+                .search_with(Needle::Text("uni"), false, options)
+                .unwrap()
+                .unwrap()
+                .len(),
+            2
+        );
+        let nocase = options.ignore_case(true);
+        assert_eq!(
+            index
+                .search_with(Needle::Text("uni"), false, nocase)
+                .unwrap()
+                .unwrap()
+                .len(),
+            5
+        );
+        let whole = nocase.ignore_accents(true).whole_word(true);
+        assert_eq!(
+            index
+                .search_with(Needle::Text("universite"), false, whole)
+                .unwrap()
+                .unwrap()
+                .len(),
+            3
+        );
+        assert_eq!(
+            index
+                .search_with(Needle::Regex("^gen"), false, whole)
+                .unwrap(),
+            None
+        );
+        let hits = index
+            .search_with(Needle::Regex("^geneve"), false, whole)
+            .unwrap();
+        assert_eq!(hits.unwrap().len(), 1);
+
+        for hit in index
+            .search_spans(Needle::Text("GENEVE"), false, whole)
+            .unwrap()
+        {
+            let shift = index.shift(hit.index).unwrap().to_string();
+            assert_eq!(&shift[hit.spans[0].range()], "Genève");
+        }
+    }
+
+    #[test]
+    fn test_entries() {
+        let mut index = ShiftIndex::new(vec![
+            Line::new("b a", Some("http://x.cn".into())),
+            Line::new("c", None),
+        ]);
+
+        let entry = index.entry(0).unwrap();
+        assert_eq!(entry.text, "a b");
+        assert_eq!(entry.url.as_deref(), Some("http://x.cn"));
+        assert_eq!((entry.source_line, entry.rotation_offset), (0, 1));
+        assert_eq!(entry.keyword, "a");
+        assert!(index.entry(3).is_err());
+
+        let entries = index.entries();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[2].url, None);
+        assert_eq!(entries[2].keyword, "c");
+
+        let hits = index
+            .search_spans(Needle::Text("b"), true, SearchOptions::new())
+            .unwrap();
+        let matches = index.matches(hits.clone());
+        assert!(matches
+            .iter()
+            .zip(&hits)
+            .all(|(m, hit)| m.index == hit.index
+                && m.spans == hit.spans
+                && m.entry == entries[m.index]));
+    }
+
+    #[test]
+    fn test_lookup() {
+        let lines = [
+            "Beijing Institute of Technology",
+            "beijing",
+            "Tsinghua",
+            "Bei",
+        ];
+        let mut index = ShiftIndex::new(lines.iter().map(|s| Line::new(s, None)).collect());
+        let shifts = index.shifts();
+
+        for prefix in ["Bei", "be", "Beijing ", "T", "of T", "zz", ""] {
+            let range = index.lookup(prefix).unwrap();
+            let expect: Vec<usize> = (0..shifts.len())
+                .filter(|&i| shifts[i].starts_with(prefix))
+                .collect();
+            assert_eq!(range.collect::<Vec<_>>(), expect, "{prefix:?}");
+        }
+
+        index.set_collator(Box::new(crate::collate::CaseInsensitive));
+        let shifts = index.shifts();
+        let range = index.lookup("BEIJING").unwrap();
+        assert_eq!(range.len(), 2);
+        assert!(shifts[range]
+            .iter()
+            .all(|s| s.to_lowercase().starts_with("beijing")));
+
+        index.set_collator(Box::new(crate::collate::Unicode::new()));
+        let shifts = index.shifts();
+        let range = index.lookup("beij").unwrap();
+        assert_eq!(range.len(), 2);
+        assert!(shifts[range]
+            .iter()
+            .all(|s| s.to_lowercase().starts_with("beijing")));
+
+        index.set_collator(crate::collate::from_spec("natural").unwrap());
+        let shifts = index.shifts();
+        let range = index.lookup("Beijing").unwrap();
+        assert_eq!(shifts[range], ["Beijing Institute of Technology"]);
+
+        // 以同一前缀开头的序列须连续排列，二分查找的结果与逐个比较一致
+        let lines = [
+            "Chapter 2 résumé",
+            "chapter 10 Resume",
+            "Chapter 1 rèsume",
+            "Chapter 01",
+        ];
+        let mut index = ShiftIndex::new(lines.iter().map(|s| Line::new(s, None)).collect());
+        for spec in ["unicode", "unicode:nocase", "natural", "natural:unicode"] {
+            index.set_collator(crate::collate::from_spec(spec).unwrap());
+            for prefix in ["Chapter 1", "chap", "resu", "1", "2 R", "Résumé", ""] {
+                let range = index.lookup(prefix).unwrap();
+                let marks = index.marks().to_vec();
+                let expect: Vec<usize> = (0..marks.len())
+                    .filter(|&i| {
+                        let shift = index.shift_of(marks[i]);
+                        index.collator.compare_prefix(shift, prefix) == Ordering::Equal
+                    })
+                    .collect();
+                assert_eq!(range.collect::<Vec<_>>(), expect, "{spec} {prefix:?}");
+            }
+        }
+
+        // 自然排序时前缀中的数字按数值匹配完整的数字片段，"Chapter 1"不会找到"Chapter 12"
+        let lines = ["Chapter 12", "Chapter 1 rèsume", "Chapter 01"];
+        let mut index = ShiftIndex::new(lines.iter().map(|s| Line::new(s, None)).collect());
+        index.set_collator(crate::collate::from_spec("natural").unwrap());
+        let shifts = index.shifts();
+        let range = index.lookup("Chapter 1").unwrap();
+        assert_eq!(shifts[range], ["Chapter 01", "Chapter 1 rèsume"]);
+    }
+
+    #[test]
+    fn test_suggest() {
+        let lines = [
+            "Beijing Institute of Technology",
+            "Beijing Normal University",
+            "Peking University",
+            "Bei",
+        ];
+        let mut index = ShiftIndex::new(lines.iter().map(|s| Line::new(s, None)).collect());
+
+        let suggest = index.suggest("Bei", 10).unwrap();
+        assert_eq!(
+            suggest,
+            [("Beijing".to_string(), 2), ("Bei".to_string(), 1)]
+        );
+
+        let suggest = index.suggest("Beijing ", 10).unwrap();
+        assert_eq!(suggest.len(), 2);
+        assert!(suggest.contains(&("Beijing Normal".to_string(), 1)));
+
+        assert_eq!(
+            index.suggest("U", 1).unwrap(),
+            [("University".to_string(), 2)]
+        );
+        assert!(index.suggest("zz", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_wildcard_search() {
+        let lines = ["Beijing Institute of Technology", "technology of beijing"];
+        let mut index = ShiftIndex::new(lines.iter().map(|s| Line::new(s, None)).collect());
+        index.set_stop_words(StopWords::english());
+
+        let shifts = index.shifts();
+        let found = |index: &mut ShiftIndex, pattern| -> Vec<String> {
+            index
+                .wildcard_search(pattern)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|i| shifts[i].clone())
+                .collect()
+        };
+
+        assert_eq!(
+            found(&mut index, "*ology"),
+            ["technology of beijing", "Technology Beijing Institute of"]
+        );
+        assert_eq!(found(&mut index, "be*ng"), ["beijing technology of"]);
+        assert!(found(&mut index, "o*").is_empty());
+        assert_eq!(index.wildcard_search("*"), Some((0..5).collect()));
+    }
+
+    #[test]
+    fn test_fuzzy_search() {
+        let lines = ["Beijing Institute of Technology", "Peking University"];
+        let mut index = ShiftIndex::new(lines.iter().map(|s| Line::new(s, None)).collect());
+
+        let hits = index.fuzzy_search("Universty", 1);
+        assert_eq!(hits.len(), 2);
+        assert!(hits.iter().all(|hit| hit.distance == 1));
+        let text = index.shift(hits[0].index).unwrap().to_string();
+        assert_eq!(&text[hits[0].spans[0].range()], "University");
+
+        // 距离小的排在前面
+        let lines = ["Pekin duck", "Peking University"];
+        let mut index = ShiftIndex::new(lines.iter().map(|s| Line::new(s, None)).collect());
+        let hits = index.fuzzy_search("Peking", 1);
+        let distances: Vec<usize> = hits.iter().map(|hit| hit.distance).collect();
+        assert_eq!(distances, [0, 0, 1, 1]);
+        assert!(index.fuzzy_search("Tsinghua", 2).is_empty());
+    }
+
+    #[test]
+    fn test_query() {
+        let lines = vec![
+            Line::new(
+                "Beijing Institute of Technology",
+                Some("bit.edu.cn".to_string()),
+            ),
+            Line::new("Peking University", Some("pku.edu.cn".to_string())),
+            Line::new("Tsinghua University", None),
+        ];
+        let mut index = ShiftIndex::new(lines);
+
+        let mut found = |q: &str| -> Vec<String> {
+            let indices = index.query(&q.parse().unwrap()).unwrap_or_default();
+            indices
+                .into_iter()
+                .map(|i| index.shift(i).unwrap().to_string())
+                .collect()
+        };
+
+        assert_eq!(
+            found("keyword:University"),
+            ["University Peking", "University Tsinghua"]
+        );
+        assert_eq!(
+            found("University NOT url:edu"),
+            ["Tsinghua University", "University Tsinghua"]
+        );
+        assert_eq!(
+            found("keyword:Beijing OR (url:pku keyword:Peking)"),
+            ["Beijing Institute of Technology", "Peking University"]
+        );
+        assert_eq!(
+            found("\"Institute of\" keyword:Technology"),
+            ["Technology Beijing Institute of"]
+        );
+        assert!(found("\"Technology Beijing\"").is_empty());
+        assert!(found("text:edu").is_empty());
+    }
+
+    #[test]
+    fn test_near() {
+        let lines = [
+            "Beijing Institute of Science and Technology",
+            "Technology Institute",
+        ];
+        let mut index = ShiftIndex::new(lines.iter().map(|s| Line::new(s, None)).collect());
+
+        let mut found = |distance: usize, ordered: bool| -> Vec<String> {
+            let indices = index
+                .near("Institute", "Technology", distance, ordered)
+                .unwrap_or_default();
+            indices
+                .into_iter()
+                .map(|i| index.shift(i).unwrap().to_string())
+                .collect()
+        };
+
+        assert_eq!(found(1, false), ["Technology Institute"]);
+        assert!(found(1, true).is_empty());
+        assert_eq!(
+            found(4, true),
+            [
+                "Beijing Institute of Science and Technology",
+                "Institute of Science and Technology Beijing",
+            ]
+        );
+
+        let line = Line::new("a b c a", None);
+        assert_eq!(
+            line.find_near("a", "c", 1, false),
+            [true, true, true, false]
+        );
+        assert_eq!(
+            line.find_near("a", "c", 2, true),
+            [true, false, false, true]
+        );
+        assert_eq!(
+            line.find_near("a", "a", 3, false),
+            [true, false, false, false]
+        );
+        assert_eq!(line.find_near("a", "x", 3, false), [false; 4]);
+    }
+
+    #[test]
+    fn test_token_search() {
+        let lines = [
+            "Beijing Institute of Technology",
+            "Beijing Normal University",
+        ];
+        let mut index = ShiftIndex::new(lines.iter().map(|s| Line::new(s, None)).collect());
+
+        let mut found = |pattern: &str| -> Vec<String> {
+            let hits = index.token_search(&pattern.parse().unwrap());
+            hits.iter()
+                .map(|hit| {
+                    let text = index.shift(hit.index).unwrap().to_string();
+                    let words: Vec<&str> =
+                        hit.spans.iter().map(|span| &text[span.range()]).collect();
+                    format!("{text}: {}", words.join(","))
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            found(r#"[word="Bei.*"] [] [word="(?i)of"]"#),
+            [
+                "Beijing Institute of Technology: Beijing Institute of",
+                "Technology Beijing Institute of: Beijing Institute of",
+            ]
+        );
+        // 匹配不能跨越原行末尾
+        assert!(found(r#""Technology" "Beijing""#).is_empty());
+        assert_eq!(
+            found(r#""Normal" [word!="B.*"]+"#),
+            [
+                "Beijing Normal University: Normal University",
+                "Normal University Beijing: Normal University",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_threads() {
+        let lines = |n: usize| {
+            (0..n)
+                .map(|i| Line::new(&format!("w{} x{} y{}", i % 7, i % 13, i), None))
+                .collect::<Vec<Line>>()
+        };
+
+        let mut single = ShiftIndex::new(lines(500));
+        single.set_threads(1).unwrap();
+        assert_eq!(single.threads(), 1);
+
+        let mut multi = ShiftIndex::new(lines(500));
+        multi.set_threads(4).unwrap();
+        assert_eq!(multi.threads(), 4);
+
+        let shifts = single.shifts();
+        assert_eq!(shifts.len(), 1500);
+        assert_eq!(shifts, multi.shifts());
+        assert!(shifts.windows(2).all(|w| magic(&w[0], &w[1]).is_le()));
+
+        // 并行搜索的结果与单线程相同，且保持排序后的顺序
+        let found = single.regex_search(r"x1\d? y", true).unwrap().unwrap();
+        assert!(found.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(Some(found), multi.regex_search(r"x1\d? y", true).unwrap());
+        assert_eq!(single.search("w3 x", false), multi.search("w3 x", false));
+        assert_eq!(
+            single
+                .search_spans(Needle::Regex("y4."), false, SearchOptions::new())
+                .unwrap(),
+            multi
+                .search_spans(Needle::Regex("y4."), false, SearchOptions::new())
+                .unwrap()
+        );
+        assert_eq!(
+            single.search_many(&["x1", "y42"], true).unwrap(),
+            multi.search_many(&["x1", "y42"], true).unwrap()
+        );
+    }
+}
@@ -2,11 +2,19 @@
 //!
 //! 提供迭代器[`RapidShifterIter`]及其实现
 //!
-//! 注册Python类[`RapidShifter`]和[`RapidShifterLines`]，处于Python模块`rshifter`下
-//! 将Rust结构体(`struct`)注册为Python类(`class`)
+//! 核心结构体[`RapidShifter`]和[`RapidShifterLines`]为纯Rust实现，可以直接作为rlib使用
+//! 启用`python`特性后，会在Python模块`rshifter`下注册同名的Python类(`class`)
 //!
 //! # Example:
 //!
+//! 以下为Rust示例代码
+//! ```rust
+//! use rshifter::RapidShifter;
+//!
+//! let mut tst = RapidShifter::new(String::from("aaa bbb ccc"));
+//! assert_eq!(tst.shifts(), ["aaa bbb ccc", "bbb ccc aaa", "ccc aaa bbb"]);
+//! ```
+//!
 //! 以下为Python示例代码
 //! ```python
 //! from rshifter import RapidShifter, RapidShifterLines
@@ -35,12 +43,26 @@
 //! ]
 //! assert tst2[0] == res2
 //! ```
-use crate::shifter::{RapidShifter, RapidShifterLines};
-use pyo3::prelude::*;
 use std::collections::VecDeque;
 
+pub mod collate;
 pub mod color;
+pub mod cql;
+pub mod error;
+mod fuzzy;
+mod index;
+mod magic;
+mod permuterm;
+#[cfg(feature = "python")]
+mod python;
+pub mod query;
+pub mod search;
 pub mod shifter;
+pub mod stopwords;
+
+pub use crate::error::{Error, Result};
+pub use crate::index::{Shift, ShiftEntry};
+pub use crate::shifter::{RapidShifter, RapidShifterLines};
 
 /// 移位方向枚举
 /// 用于迭代器[`RapidShifterIter`]，来确定移位的方向
@@ -52,7 +74,8 @@ pub enum Direction {
 }
 
 /// 迭代器`RapidShifterIter`
-/// 用于产生所有的移位序列，在结构体[`RapidShifter`]和[`RapidShifterLines`]中使用
+/// 用于直接产生拼接后的所有移位序列
+/// 结构体[`RapidShifter`]和[`RapidShifterLines`]改为存储标记，不再使用本迭代器，参见[`Shift`]
 ///
 /// 通过双端队列[`VecDeque`]来高效操作，`direction`用来确定移位方向
 /// `direction`类型为[`Direction`]，有两种选择
@@ -70,7 +93,11 @@ impl RapidShifterIter<'_> {
     /// `length`类型为[`Option<usize>`]，当值为[`None`]时，产生的移位序列内容和方向无关
     /// `direction`类型为[`Direction`]，其值用于决定采用方法[`VecDeque::rotate_left`]还是[`VecDeque::rotate_right`]进行移位
     #[inline]
-    pub fn new(input: Vec<&str>, length: Option<usize>, direction: Direction) -> RapidShifterIter {
+    pub fn new(
+        input: Vec<&str>,
+        length: Option<usize>,
+        direction: Direction,
+    ) -> RapidShifterIter<'_> {
         let length = length.unwrap_or(input.len());
         RapidShifterIter {
             queue: VecDeque::from(input),
@@ -98,17 +125,12 @@ impl Iterator for RapidShifterIter<'_> {
     }
 }
 
-#[pymodule]
-fn rshifter(m: &Bound<'_, PyModule>) -> PyResult<()> {
-    m.add_class::<RapidShifter>()?;
-    m.add_class::<RapidShifterLines>()?;
-    Ok(())
-}
-
 #[cfg(test)]
 mod test {
+    use crate::collate::Bytewise;
+    use crate::stopwords::StopWords;
     use crate::RapidShifter;
-    //use crate::RapidShifterLines;
+    use crate::RapidShifterLines;
 
     use regex::Regex;
 
@@ -165,6 +187,63 @@ mod test {
         assert_eq!(tst, res);
     }
 
+    #[test]
+    fn test_threads() {
+        let input: Vec<String> = [
+            "aaa bbb ccc ddd",
+            "a A p p b B a W R P",
+            "A simple test sentence",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        let tst = RapidShifterLines::new(input.clone())
+            .with_threads(2)
+            .unwrap()
+            .shifts();
+        assert_eq!(tst, RapidShifterLines::new(input).shifts());
+    }
+
+    #[test]
+    fn test_collator() {
+        let input = vec![String::from("b A"), String::from("a B")];
+
+        let tst = RapidShifterLines::new(input.clone()).shifts().join(",");
+        assert_eq!(tst, "a B,A b,b A,B a");
+
+        let tst = RapidShifterLines::new(input)
+            .with_collator(Box::new(Bytewise))
+            .shifts()
+            .join(",");
+        assert_eq!(tst, "A b,B a,a B,b A");
+    }
+
+    #[test]
+    fn test_stop_words() {
+        let input = String::from("beijing institute of technology");
+
+        let tst = RapidShifter::new(input)
+            .with_stop_words(StopWords::english())
+            .shifts()
+            .to_owned();
+        assert_eq!(
+            tst,
+            [
+                "beijing institute of technology",
+                "institute of technology beijing",
+                "technology beijing institute of",
+            ]
+        );
+
+        let input = vec![String::from("The Art of War"), String::from("of the")];
+        let tst = RapidShifterLines::new(input)
+            .with_stop_words(StopWords::english())
+            .shifts()
+            .join(",");
+        assert_eq!(tst, "Art of War The,War The Art of");
+    }
+
     #[test]
     fn test_reg() {
         let re = Regex::new(r"^(https?|ftp)://[^\s/$.?#].[^\s]*$").unwrap();
@@ -0,0 +1,288 @@
+//! 移位序列排序函数[`magic`]的实现模块
+//!
+//! 按"a < A < b < B < c < C ..."顺序比较两个字符串
+//! 原理参见Python版本注释：将每个字节循环左移3位再异或1得到排序键[`key`]
+//!
+//! 由于[`key`]是字节上的双射，两个字符串第一个不同的字节即决定了排序结果，
+//! 因此只需要找到第一个不同字节的位置，再比较该位置的排序键即可
+//! 查找过程有以下几种实现，在首次调用时根据CPU特性选择：
+//! - [`mismatch_swar`]：可移植的安全实现，每次比较8个字节
+//! - `mismatch_avx2`：仅x86_64，需要CPU支持AVX2，每次比较32个字节
+
+use std::cmp::Ordering;
+use std::sync::OnceLock;
+
+/// 查找函数类型，返回第一个不同字节的位置（若没有则返回较短字符串的长度）
+type Mismatch = fn(&[u8], &[u8]) -> usize;
+
+/// 运行时选择的查找函数
+static MISMATCH: OnceLock<Mismatch> = OnceLock::new();
+
+/// 单个字节的排序键
+#[inline(always)]
+pub(crate) const fn key(byte: u8) -> u8 {
+    byte.rotate_left(3) ^ 0x1
+}
+
+/// 移位序列排序函数
+/// 可以按"a < A < b < B < c < C ..."顺序进行排序
+///
+/// > 注意：若一个字符串是另一个的前缀，较长的字符串排在前面
+#[inline]
+pub(crate) fn magic(x: &str, y: &str) -> Ordering {
+    magic_bytes(x.as_bytes(), y.as_bytes())
+}
+
+/// 字节序列版本的[`magic`]
+#[inline]
+pub(crate) fn magic_bytes(x: &[u8], y: &[u8]) -> Ordering {
+    let mismatch = MISMATCH.get_or_init(select);
+
+    let n = mismatch(x, y);
+    if n < x.len() && n < y.len() {
+        key(x[n]).cmp(&key(y[n]))
+    } else {
+        y.len().cmp(&x.len())
+    }
+}
+
+/// 分段版本的[`magic`]，等价于比较各片段依次拼接后的字节序列
+/// 用于不拼接字符串直接比较移位序列
+pub(crate) fn magic_parts(x: &[&[u8]], y: &[&[u8]]) -> Ordering {
+    let mismatch = MISMATCH.get_or_init(select);
+
+    let (mut px, mut py) = (x.iter().copied(), y.iter().copied());
+    let (mut cx, mut cy) = (px.next().unwrap_or_default(), py.next().unwrap_or_default());
+
+    loop {
+        if cx.is_empty() {
+            match px.next() {
+                Some(part) => cx = part,
+                None => break,
+            }
+            continue;
+        }
+        if cy.is_empty() {
+            match py.next() {
+                Some(part) => cy = part,
+                None => break,
+            }
+            continue;
+        }
+
+        let n = mismatch(cx, cy);
+        if n < cx.len() && n < cy.len() {
+            return key(cx[n]).cmp(&key(cy[n]));
+        }
+        (cx, cy) = (&cx[n..], &cy[n..]);
+    }
+
+    let rest =
+        |c: &[u8], p: &mut dyn Iterator<Item = &[u8]>| c.len() + p.map(<[u8]>::len).sum::<usize>();
+    rest(cy, &mut py).cmp(&rest(cx, &mut px))
+}
+
+/// 逐字节比较的参考实现，与最初的内联汇编版本行为一致
+#[cfg(test)]
+fn magic_scalar(x: &str, y: &str) -> Ordering {
+    for (dx, dy) in x.bytes().zip(y.bytes()) {
+        match key(dx).cmp(&key(dy)) {
+            Ordering::Equal => continue,
+            ord => return ord,
+        }
+    }
+
+    y.len().cmp(&x.len())
+}
+
+/// 根据CPU特性选择查找函数
+fn select() -> Mismatch {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        return avx2::mismatch;
+    }
+
+    mismatch_swar
+}
+
+/// 可移植的查找实现，每次比较8个字节
+fn mismatch_swar(x: &[u8], y: &[u8]) -> usize {
+    let len = x.len().min(y.len());
+    let (x, y) = (&x[..len], &y[..len]);
+
+    let mut offset = 0;
+    for (cx, cy) in x.chunks_exact(8).zip(y.chunks_exact(8)) {
+        let wx = u64::from_le_bytes(cx.try_into().unwrap());
+        let wy = u64::from_le_bytes(cy.try_into().unwrap());
+
+        let diff = wx ^ wy;
+        if diff != 0 {
+            return offset + diff.trailing_zeros() as usize / 8;
+        }
+        offset += 8;
+    }
+
+    offset
+        + x[offset..]
+            .iter()
+            .zip(&y[offset..])
+            .position(|(dx, dy)| dx != dy)
+            .unwrap_or(len - offset)
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use std::arch::x86_64::*;
+
+    /// AVX2查找实现，仅在[`super::select`]检测到CPU支持AVX2时使用
+    pub(super) fn mismatch(x: &[u8], y: &[u8]) -> usize {
+        // SAFETY: `select` only hands out this function after detecting AVX2
+        unsafe { mismatch_avx2(x, y) }
+    }
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn mismatch_avx2(x: &[u8], y: &[u8]) -> usize {
+        let len = x.len().min(y.len());
+
+        let mut offset = 0;
+        while offset + 32 <= len {
+            // SAFETY: `offset + 32 <= len`, loads are unaligned
+            let (vx, vy) = unsafe {
+                (
+                    _mm256_loadu_si256(x.as_ptr().add(offset) as *const __m256i),
+                    _mm256_loadu_si256(y.as_ptr().add(offset) as *const __m256i),
+                )
+            };
+
+            let eq = _mm256_movemask_epi8(_mm256_cmpeq_epi8(vx, vy)) as u32;
+            if eq != u32::MAX {
+                return offset + (!eq).trailing_zeros() as usize;
+            }
+            offset += 32;
+        }
+
+        offset + super::mismatch_swar(&x[offset..len], &y[offset..len])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// 简单的线性同余生成器，生成可复现的测试数据
+    fn lcg(seed: &mut u64) -> u64 {
+        *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        *seed >> 33
+    }
+
+    fn random_string(seed: &mut u64, alphabet: &[char]) -> String {
+        let len = lcg(seed) % 80;
+        (0..len)
+            .map(|_| alphabet[lcg(seed) as usize % alphabet.len()])
+            .collect()
+    }
+
+    #[test]
+    fn test_key_order() {
+        let mut letters: Vec<&str> = vec!["B", "b", "A", "a", "C", "c", " "];
+        letters.sort_unstable_by(|x, y| magic(x, y));
+        assert_eq!(letters, [" ", "a", "A", "b", "B", "c", "C"]);
+    }
+
+    #[test]
+    fn test_prefix_order() {
+        assert_eq!(magic("ab", "abc"), Ordering::Greater);
+        assert_eq!(magic("abc", "ab"), Ordering::Less);
+        assert_eq!(magic("abc", "abc"), Ordering::Equal);
+        assert_eq!(magic("", ""), Ordering::Equal);
+    }
+
+    #[test]
+    #[allow(clippy::manual_rotate)]
+    fn test_all_bytes() {
+        // 与最初内联汇编中的`rol 3; xor 0x1`逐条对应
+        let asm = |b: u8| ((b << 3) | (b >> 5)) ^ 0x1;
+
+        for x in 0..=u8::MAX {
+            for y in 0..=u8::MAX {
+                let expect = asm(x).cmp(&asm(y));
+                assert_eq!(magic_bytes(&[b'q', x], &[b'q', y]), expect, "{x:#x} {y:#x}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_same_as_scalar() {
+        let alphabet: Vec<char> = "aAbBzZ 09_é中".chars().collect();
+        let mut seed = 0x5eed;
+
+        for _ in 0..5000 {
+            let x = random_string(&mut seed, &alphabet);
+            // 构造长公共前缀，覆盖分块比较的各个边界
+            let prefix: String = x.chars().take(x.chars().count() / 2).collect();
+            let y = prefix + &random_string(&mut seed, &alphabet);
+            assert_eq!(magic(&x, &y), magic_scalar(&x, &y), "{x:?} {y:?}");
+            assert_eq!(magic(&y, &x), magic_scalar(&y, &x), "{y:?} {x:?}");
+            assert_eq!(magic(&x, &x), Ordering::Equal);
+        }
+    }
+
+    #[test]
+    fn test_parts() {
+        let alphabet: Vec<char> = "aAbB ".chars().collect();
+        let mut seed = 7;
+
+        for _ in 0..2000 {
+            let (x, y) = (
+                random_string(&mut seed, &alphabet),
+                random_string(&mut seed, &alphabet),
+            );
+            let (sx, sy) = (
+                lcg(&mut seed) as usize % (x.len() + 1),
+                lcg(&mut seed) as usize % (y.len() + 1),
+            );
+            let (bx, by) = (x.as_bytes(), y.as_bytes());
+
+            let parts_x: [&[u8]; 3] = [&bx[..sx], b"", &bx[sx..]];
+            let parts_y: [&[u8]; 2] = [&by[..sy], &by[sy..]];
+            assert_eq!(
+                magic_parts(&parts_x, &parts_y),
+                magic(&x, &y),
+                "{x:?} {y:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_mismatch_impls() {
+        let x = "ab".repeat(50);
+        for n in 0..x.len() {
+            let mut y = x.clone().into_bytes();
+            y[n] = b'_';
+
+            assert_eq!(mismatch_swar(x.as_bytes(), &y), n);
+            assert_eq!(mismatch_swar(&x.as_bytes()[..n], &y), n);
+
+            #[cfg(target_arch = "x86_64")]
+            if is_x86_feature_detected!("avx2") {
+                assert_eq!(avx2::mismatch(x.as_bytes(), &y), n);
+                assert_eq!(avx2::mismatch(&x.as_bytes()[..n], &y), n);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sort_like_scalar() {
+        let alphabet: Vec<char> = "aAbB ".chars().collect();
+        let mut seed = 42;
+
+        let mut fast: Vec<String> = (0..500)
+            .map(|_| random_string(&mut seed, &alphabet))
+            .collect();
+        let mut slow = fast.clone();
+
+        fast.sort_by(|x, y| magic(x, y));
+        slow.sort_by(|x, y| magic_scalar(x, y));
+        assert_eq!(fast, slow);
+    }
+}
@@ -0,0 +1,408 @@
+//! 命令行程序`rshifter`
+//!
+//! 不依赖Python解释器，直接调用[`RapidShifter`]与[`RapidShifterLines`]
+//! 功能与`qshifter.py`一致：循环移位字符串或文件，并支持搜索/正则搜索
+//!
+//! 需要启用`cli`特性：`cargo install --path . --features cli`，核心库与Python模块不依赖clap
+//!
+//! 退出码可以在shell脚本中使用：
+//! - `0`：成功，或搜索找到匹配序列
+//! - `1`：搜索未找到匹配序列
+//! - `2`：参数/输入错误（文件不存在、正则表达式非法等）
+//!
+//! # Example:
+//!
+//! ```shell
+//! rshifter -p "aaa bbb ccc"
+//! rshifter -f test/test_file.txt -v
+//! rshifter -f test/test_file.txt --search Beijing -a
+//! cat test/test_file.txt | rshifter --regex-search "^B.*g"
+//! rshifter -f test/test_file.txt --search beijing --ignore-case --whole-word
+//! rshifter -f test/test_file.txt --marker /
+//! rshifter -f test/test_file.txt --wildcard "*ology"
+//! rshifter -f test/test_file.txt --fuzzy Universty --distance 1
+//! rshifter -f test/test_file.txt --query 'keyword:Beijing OR "of Technology"'
+//! rshifter -f test/test_file.txt --near Institute Technology --distance 3 --ordered
+//! rshifter -f test/test_file.txt --tokens '[word="Bei.*"] [] [word="(?i)technology"]'
+//! ```
+
+use std::fs;
+use std::io::{self, IsTerminal, Read};
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use rshifter::collate;
+use rshifter::color::Color;
+use rshifter::search::{Needle, SearchHit, SearchOptions};
+use rshifter::stopwords::StopWords;
+use rshifter::{RapidShifter, RapidShifterLines, Result};
+
+/// 找到匹配序列或处理成功
+const EXIT_OK: u8 = 0;
+/// 搜索未找到匹配序列
+const EXIT_NOT_FOUND: u8 = 1;
+/// 参数/输入错误
+const EXIT_ERROR: u8 = 2;
+
+/// 将输入的字符串按单词循环移位
+#[derive(Parser)]
+#[command(version)]
+struct Args {
+    /// 详细模式
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// 搜索特定字符串
+    #[arg(long, conflicts_with = "regex_search")]
+    search: Option<String>,
+
+    /// 使用正则搜索特定字符串
+    #[arg(long, conflicts_with = "wildcard")]
+    regex_search: Option<String>,
+
+    /// 通配符搜索开头单词，如 tech*, *ology, be*ng
+    #[arg(short, long, conflicts_with = "search")]
+    wildcard: Option<String>,
+
+    /// 模糊搜索包含与该单词相近的单词的序列
+    #[arg(
+        long,
+        group = "proximity",
+        conflicts_with_all = ["search", "regex_search", "wildcard"]
+    )]
+    fuzzy: Option<String>,
+
+    /// 按查询语言搜索，如 'keyword:Beijing AND NOT url:example'
+    #[arg(
+        short,
+        long,
+        conflicts_with_all = ["search", "regex_search", "wildcard", "fuzzy"]
+    )]
+    query: Option<String>,
+
+    /// 按单词级模式搜索，如 '[word="Bei.*"] [] [word="(?i)technology"]'
+    #[arg(
+        short,
+        long,
+        conflicts_with_all = ["search", "regex_search", "wildcard", "fuzzy", "query"]
+    )]
+    tokens: Option<String>,
+
+    /// 邻近搜索两个单词在原行中相距不超过 --distance 个单词的序列
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["A", "B"],
+        group = "proximity",
+        conflicts_with_all = ["search", "regex_search", "wildcard", "fuzzy", "query", "tokens"]
+    )]
+    near: Option<Vec<String>>,
+
+    /// 邻近搜索时第一个单词须在第二个单词之前
+    #[arg(long, requires = "near")]
+    ordered: bool,
+
+    /// 模糊搜索允许的最大编辑距离，或邻近搜索允许的最大单词距离
+    #[arg(long, default_value_t = 1, requires = "proximity")]
+    distance: usize,
+
+    /// 搜索包括网址URL
+    #[arg(short, long)]
+    all: bool,
+
+    /// 搜索时全词匹配
+    #[arg(long)]
+    whole_word: bool,
+
+    /// 搜索时忽略大小写
+    #[arg(short, long)]
+    ignore_case: bool,
+
+    /// 搜索时忽略重音
+    #[arg(long)]
+    ignore_accents: bool,
+
+    /// 搜索时允许匹配跨越原行末尾
+    #[arg(long)]
+    cross_boundary: bool,
+
+    /// 展示时用该字符串标记原行末尾，如 /
+    #[arg(short, long)]
+    marker: Option<String>,
+
+    /// 停用词表，可以多次给出：en, zh（仅适用于以空格分词的中文）或停用词文件路径
+    #[arg(short, long = "stop-words", value_name = "LIST")]
+    stop_words: Vec<String>,
+
+    /// 排序规则：magic, bytes, nocase, alphabet:<字母表>, unicode[:nocase,noaccent], pinyin, natural[:<排序规则>]
+    #[arg(short, long, default_value = "magic")]
+    collation: String,
+
+    /// 多行输入时并发处理的线程数，0表示使用全部CPU核心
+    #[arg(short = 'j', long, default_value_t = 0)]
+    threads: usize,
+
+    /// 需要循环移位的字符串
+    #[arg(short = 'p', long, conflicts_with = "file")]
+    input: Option<String>,
+
+    /// 以文件格式输入，每行为一个字符串（均未给出时从标准输入读取）
+    #[arg(short, long)]
+    file: Option<String>,
+}
+
+/// 单行/多行循环移位器
+enum Shifter {
+    Line(RapidShifter),
+    Lines(RapidShifterLines),
+}
+
+impl Shifter {
+    fn with_marker(self, marker: String) -> Self {
+        match self {
+            Shifter::Line(shifter) => Shifter::Line(shifter.with_marker(marker)),
+            Shifter::Lines(shifter) => Shifter::Lines(shifter.with_marker(marker)),
+        }
+    }
+
+    fn show_all(&mut self, verbose: bool) {
+        match self {
+            Shifter::Line(shifter) => shifter.show_all(verbose),
+            Shifter::Lines(shifter) => shifter.show_all(verbose),
+        }
+    }
+
+    fn search(
+        &mut self,
+        needle: Needle<'_>,
+        all: bool,
+        options: SearchOptions,
+    ) -> Result<Vec<SearchHit>> {
+        match self {
+            Shifter::Line(shifter) => shifter.search_spans(needle, all, options),
+            Shifter::Lines(shifter) => shifter.search_spans(needle, all, options),
+        }
+    }
+
+    fn wildcard_search(&mut self, pattern: &str) -> Vec<SearchHit> {
+        let result = match self {
+            Shifter::Line(shifter) => shifter.wildcard_search(pattern),
+            Shifter::Lines(shifter) => shifter.wildcard_search(pattern),
+        };
+
+        result
+            .unwrap_or_default()
+            .into_iter()
+            .map(|index| SearchHit {
+                index,
+                spans: Vec::new(),
+            })
+            .collect()
+    }
+
+    fn fuzzy_search(&mut self, term: &str, max_distance: usize) -> Vec<SearchHit> {
+        let hits = match self {
+            Shifter::Line(shifter) => shifter.fuzzy_search(term, max_distance),
+            Shifter::Lines(shifter) => shifter.fuzzy_search(term, max_distance),
+        };
+
+        hits.into_iter().map(SearchHit::from).collect()
+    }
+
+    fn query(&mut self, query: &str) -> Result<Vec<SearchHit>> {
+        let result = match self {
+            Shifter::Line(shifter) => shifter.query(query)?,
+            Shifter::Lines(shifter) => shifter.query(query)?,
+        };
+
+        Ok(result
+            .unwrap_or_default()
+            .into_iter()
+            .map(|index| SearchHit {
+                index,
+                spans: Vec::new(),
+            })
+            .collect())
+    }
+
+    fn near(&mut self, a: &str, b: &str, distance: usize, ordered: bool) -> Vec<SearchHit> {
+        let result = match self {
+            Shifter::Line(shifter) => shifter.near(a, b, distance, ordered),
+            Shifter::Lines(shifter) => shifter.near(a, b, distance, ordered),
+        };
+
+        result
+            .unwrap_or_default()
+            .into_iter()
+            .map(|index| SearchHit {
+                index,
+                spans: Vec::new(),
+            })
+            .collect()
+    }
+
+    fn token_search(&mut self, pattern: &str) -> Result<Vec<SearchHit>> {
+        match self {
+            Shifter::Line(shifter) => shifter.token_search_spans(pattern),
+            Shifter::Lines(shifter) => shifter.token_search_spans(pattern),
+        }
+    }
+
+    fn get(&mut self, index: usize) -> Result<String> {
+        match self {
+            Shifter::Line(shifter) => shifter.get(index),
+            Shifter::Lines(shifter) => shifter.get(index),
+        }
+    }
+
+    fn show_hits(&mut self, hits: &[SearchHit], verbose: bool) -> Result<()> {
+        match self {
+            Shifter::Line(shifter) => shifter.show_hits(hits, verbose),
+            Shifter::Lines(shifter) => shifter.show_hits(hits, verbose),
+        }
+    }
+}
+
+fn error(msg: &str) -> ExitCode {
+    eprintln!("{} {msg}", Color::Red.color("[-]"));
+    ExitCode::from(EXIT_ERROR)
+}
+
+fn read_lines(file: Option<&str>) -> io::Result<Vec<String>> {
+    let content = match file {
+        Some(path) => fs::read_to_string(path)?,
+        None => {
+            let mut buf = String::new();
+            io::stdin().read_to_string(&mut buf)?;
+            buf
+        }
+    };
+
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// 打印搜索结果，返回对应的退出码
+/// 详细模式或输出到终端时高亮匹配位置，否则输出不含颜色的原始序列
+fn show_result(
+    shifter: &mut Shifter,
+    pat: &str,
+    hits: Vec<SearchHit>,
+    verbose: bool,
+) -> Result<ExitCode> {
+    if hits.is_empty() {
+        if verbose {
+            println!("{} 未找到匹配序列： {pat}", Color::Red.color("[-]"));
+        }
+        return Ok(ExitCode::from(EXIT_NOT_FOUND));
+    }
+
+    if verbose {
+        let nums: Vec<usize> = hits.iter().map(|hit| hit.index + 1).collect();
+        println!("{} 搜索字符串\"{pat}\"", Color::Blue.color("[*]"));
+        println!("{} 匹配序列序号：{nums:?}", Color::Green.color("[+]"));
+    }
+
+    if verbose || io::stdout().is_terminal() {
+        shifter.show_hits(&hits, verbose)?;
+    } else {
+        for hit in hits {
+            println!("{}", shifter.get(hit.index)?);
+        }
+    }
+
+    Ok(ExitCode::from(EXIT_OK))
+}
+
+/// 读取停用词表，`en`与`zh`为内置停用词表，其余视为文件路径
+fn stop_words(lists: &[String]) -> Result<StopWords> {
+    let mut stop_words = StopWords::new();
+
+    for list in lists {
+        stop_words = stop_words.union(match list.as_str() {
+            "en" => StopWords::english(),
+            "zh" => StopWords::chinese(),
+            path => StopWords::from_file(path)?,
+        });
+    }
+
+    Ok(stop_words)
+}
+
+fn run(args: Args) -> Result<ExitCode> {
+    let collator = collate::from_spec(&args.collation)?;
+    let stop_words = stop_words(&args.stop_words)?;
+
+    let mut shifter = match args.input {
+        Some(input) => Shifter::Line(
+            RapidShifter::new(input)
+                .with_collator(collator)
+                .with_stop_words(stop_words),
+        ),
+        None => match read_lines(args.file.as_deref()) {
+            Ok(lines) => Shifter::Lines(
+                RapidShifterLines::new(lines)
+                    .with_collator(collator)
+                    .with_stop_words(stop_words)
+                    .with_threads(args.threads)?,
+            ),
+            Err(err) => return Ok(error(&err.to_string())),
+        },
+    };
+    if let Some(marker) = args.marker {
+        shifter = shifter.with_marker(marker);
+    }
+
+    let options = SearchOptions::new()
+        .whole_word(args.whole_word)
+        .ignore_case(args.ignore_case)
+        .ignore_accents(args.ignore_accents)
+        .cross_boundary(args.cross_boundary);
+
+    if let Some(pat) = args.search {
+        let hits = shifter.search(Needle::Text(&pat), args.all, options)?;
+        return show_result(&mut shifter, &pat, hits, args.verbose);
+    }
+
+    if let Some(re) = args.regex_search {
+        let hits = shifter.search(Needle::Regex(&re), args.all, options)?;
+        return show_result(&mut shifter, &re, hits, args.verbose);
+    }
+
+    if let Some(pattern) = args.wildcard {
+        let hits = shifter.wildcard_search(&pattern);
+        return show_result(&mut shifter, &pattern, hits, args.verbose);
+    }
+
+    if let Some(term) = args.fuzzy {
+        let hits = shifter.fuzzy_search(&term, args.distance);
+        return show_result(&mut shifter, &term, hits, args.verbose);
+    }
+
+    if let Some(query) = args.query {
+        let hits = shifter.query(&query)?;
+        return show_result(&mut shifter, &query, hits, args.verbose);
+    }
+
+    if let Some(words) = args.near {
+        let hits = shifter.near(&words[0], &words[1], args.distance, args.ordered);
+        return show_result(&mut shifter, &words.join(" "), hits, args.verbose);
+    }
+
+    if let Some(pattern) = args.tokens {
+        let hits = shifter.token_search(&pattern)?;
+        return show_result(&mut shifter, &pattern, hits, args.verbose);
+    }
+
+    shifter.show_all(args.verbose);
+    Ok(ExitCode::from(EXIT_OK))
+}
+
+fn main() -> ExitCode {
+    run(Args::parse()).unwrap_or_else(|err| error(&err.to_string()))
+}
@@ -0,0 +1,207 @@
+//! 轮排索引(permuterm)模块
+//!
+//! 为每个单词加上结束符[`END`]后产生它的所有循环移位，排序后即可将通配符查询转换为前缀查询：
+//! - `tech*`：查找以`"\0tech"`开头的轮排
+//! - `*ology`：查找以`"ology\0"`开头的轮排
+//! - `be*ng`：查找以`"ng\0be"`开头的轮排
+//!
+//! 含有多个`*`时，先按首尾部分进行前缀查询，再逐个检查中间部分
+//!
+//! 去重后的词表按字节序排序，同时用于模糊搜索，参见[`crate::fuzzy`]
+
+use std::collections::HashMap;
+
+use crate::fuzzy;
+use crate::index::{Line, Mark};
+
+/// 单词结束符，不会出现在以空白切分的单词中
+const END: char = '\0';
+
+/// 轮排索引
+#[derive(Debug)]
+pub(crate) struct Permuterm {
+    /// 所有单词（去重），按字节序排序
+    words: Vec<String>,
+    /// 每个单词在各行中出现的位置
+    postings: Vec<Vec<Mark>>,
+    /// 所有单词加上结束符后的循环移位及其单词编号，按字节序排序
+    rotations: Vec<(String, u32)>,
+}
+
+impl Permuterm {
+    pub(crate) fn new(lines: &[Line]) -> Self {
+        let mut ids: HashMap<&str, u32> = HashMap::new();
+        let mut words = Vec::new();
+        let mut postings: Vec<Vec<Mark>> = Vec::new();
+
+        for (line, item) in lines.iter().enumerate() {
+            for offset in 0..item.len() {
+                let word = item.word(offset);
+                let id = *ids.entry(word).or_insert_with(|| {
+                    words.push(word.to_string());
+                    postings.push(Vec::new());
+                    words.len() as u32 - 1
+                });
+
+                postings[id as usize].push(Mark {
+                    line: line as u32,
+                    offset: offset as u32,
+                });
+            }
+        }
+
+        let mut order: Vec<usize> = (0..words.len()).collect();
+        order.sort_unstable_by(|&a, &b| words[a].cmp(&words[b]));
+        let words: Vec<String> = order
+            .iter()
+            .map(|&i| std::mem::take(&mut words[i]))
+            .collect();
+        let postings: Vec<Vec<Mark>> = order
+            .iter()
+            .map(|&i| std::mem::take(&mut postings[i]))
+            .collect();
+
+        let mut rotations = Vec::new();
+        for (id, word) in words.iter().enumerate() {
+            let term = format!("{word}{END}");
+            for (start, _) in term.char_indices() {
+                rotations.push((format!("{}{}", &term[start..], &term[..start]), id as u32));
+            }
+        }
+        rotations.sort_unstable();
+
+        Permuterm {
+            words,
+            postings,
+            rotations,
+        }
+    }
+
+    /// 以`prefix`开头的所有轮排对应的单词编号
+    fn prefixed<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = u32> + 'a {
+        let start = self
+            .rotations
+            .partition_point(|(rotation, _)| rotation.as_str() < prefix);
+
+        self.rotations[start..]
+            .iter()
+            .take_while(move |(rotation, _)| rotation.starts_with(prefix))
+            .map(|&(_, id)| id)
+    }
+
+    /// 与通配符`pattern`匹配的单词的所有出现位置，`*`匹配任意个字符
+    pub(crate) fn query(&self, pattern: &str) -> Vec<Mark> {
+        let parts: Vec<&str> = pattern.split('*').collect();
+
+        let (first, last) = (parts[0], parts[parts.len() - 1]);
+        let prefix = match parts.len() {
+            1 => format!("{first}{END}"),
+            // 首尾均为空时，以最长的中间部分作为前缀
+            _ if first.is_empty() && last.is_empty() => parts
+                .iter()
+                .max_by_key(|part| part.len())
+                .unwrap()
+                .to_string(),
+            _ => format!("{last}{END}{first}"),
+        };
+
+        let mut ids: Vec<u32> = self
+            .prefixed(&prefix)
+            .filter(|&id| matches(&parts, &self.words[id as usize]))
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+
+        ids.into_iter()
+            .flat_map(|id| self.postings[id as usize].iter().copied())
+            .collect()
+    }
+
+    /// 与`term`编辑距离不超过`max`的单词，返回（单词，距离，出现位置）
+    pub(crate) fn fuzzy(&self, term: &str, max: usize) -> Vec<(&str, usize, &[Mark])> {
+        fuzzy::within(&self.words, term, max)
+            .into_iter()
+            .map(|(id, distance)| {
+                (
+                    self.words[id].as_str(),
+                    distance,
+                    self.postings[id].as_slice(),
+                )
+            })
+            .collect()
+    }
+}
+
+/// 单词`word`是否与以`*`切分后的通配符`parts`匹配
+fn matches(parts: &[&str], word: &str) -> bool {
+    if let [exact] = parts {
+        return word == *exact;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if word.len() < first.len() + last.len() || !word.starts_with(first) || !word.ends_with(last) {
+        return false;
+    }
+
+    let mut rest = &word[first.len()..word.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(n) => rest = &rest[n + part.len()..],
+            None => return false,
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn words<'a>(lines: &'a [Line], index: &Permuterm, pattern: &str) -> Vec<&'a str> {
+        let mut res: Vec<&str> = index
+            .query(pattern)
+            .iter()
+            .map(|mark| lines[mark.line as usize].word(mark.offset as usize))
+            .collect();
+        res.sort_unstable();
+        res.dedup();
+        res
+    }
+
+    #[test]
+    fn test_query() {
+        let lines = [
+            Line::new("Beijing Institute of Technology", None),
+            Line::new("technology biology beijing", None),
+            Line::new("being tech", None),
+        ];
+        let index = Permuterm::new(&lines);
+
+        assert_eq!(words(&lines, &index, "tech*"), ["tech", "technology"]);
+        assert_eq!(
+            words(&lines, &index, "*ology"),
+            ["Technology", "biology", "technology"]
+        );
+        assert_eq!(words(&lines, &index, "be*ng"), ["beijing", "being"]);
+        assert_eq!(
+            words(&lines, &index, "*e*i*"),
+            ["Beijing", "beijing", "being"]
+        );
+        assert_eq!(words(&lines, &index, "*stitut*"), ["Institute"]);
+        assert_eq!(words(&lines, &index, "tech"), ["tech"]);
+        assert_eq!(words(&lines, &index, "te"), Vec::<&str>::new());
+        assert_eq!(words(&lines, &index, "bei*jing*"), ["beijing"]);
+        assert_eq!(index.query("*").len(), 9);
+        assert_eq!(index.query("technology").len(), 1);
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(matches(&["a", "b"], "ab"));
+        assert!(matches(&["ab"], "ab") && !matches(&["a"], "ab"));
+        assert!(!matches(&["ab", "ba"], "aba"));
+        assert!(matches(&["", "x", "y", ""], "axbyc"));
+        assert!(!matches(&["", "y", "x", ""], "axbyc"));
+    }
+}
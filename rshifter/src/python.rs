@@ -0,0 +1,874 @@
+//! Python绑定模块，需要启用`python`特性
+//!
+//! 将核心结构体[`RapidShifter`]与[`RapidShifterLines`]包装为Python类(`class`)
+//! 并注册在Python模块`rshifter`下，类名与Rust结构体相同
+//!
+//! 核心库返回的[`Error`]会在此处转换为对应的Python异常
+
+use std::collections::HashMap;
+
+use pyo3::create_exception;
+use pyo3::exceptions;
+use pyo3::prelude::*;
+use pyo3::types::PyRange;
+
+use crate::collate;
+use crate::error::Error;
+use crate::index::ShiftEntry;
+use crate::search::{
+    FuzzyHit, Match, Needle, Pattern, RegexOptions, SearchHit, SearchOptions, Span,
+};
+use crate::shifter::{RapidShifter, RapidShifterLines};
+use crate::stopwords::StopWords;
+
+create_exception!(rshifter, PyRegexError, pyo3::exceptions::PyException);
+create_exception!(rshifter, PyRegexSyntaxError, PyRegexError);
+create_exception!(rshifter, PyRegexCompiledTooBigError, PyRegexError);
+create_exception!(rshifter, PyQueryError, pyo3::exceptions::PyValueError);
+
+impl From<Error> for PyErr {
+    fn from(err: Error) -> Self {
+        let msg = err.to_string();
+
+        match err {
+            Error::OutOfRange(_) => exceptions::PyIndexError::new_err(msg),
+            Error::Collation(_) => exceptions::PyValueError::new_err(msg),
+            Error::Unsupported(_) => exceptions::PyNotImplementedError::new_err(msg),
+            Error::Io(err) => err.into(),
+            Error::ThreadPool(_) => exceptions::PyRuntimeError::new_err(msg),
+            Error::Query { .. } => PyQueryError::new_err(msg),
+            Error::Patterns(_) => exceptions::PyValueError::new_err(msg),
+            Error::Regex { err, .. } => match err {
+                regex::Error::CompiledTooBig(_) => PyRegexCompiledTooBigError::new_err(msg),
+                regex::Error::Syntax(_) => PyRegexSyntaxError::new_err(msg),
+                _ => PyRegexError::new_err(msg),
+            },
+        }
+    }
+}
+
+/// Python类`StopWords`，包装[`StopWords`]
+///
+/// 可以通过`|`合并多个停用词表
+#[pyclass(name = "StopWords")]
+#[derive(Clone)]
+pub struct PyStopWords(StopWords);
+
+#[pymethods]
+impl PyStopWords {
+    #[new]
+    #[pyo3(signature = (words=Vec::new()))]
+    fn new(words: Vec<String>) -> Self {
+        PyStopWords(StopWords::new().with_words(words))
+    }
+
+    #[staticmethod]
+    fn english() -> Self {
+        PyStopWords(StopWords::english())
+    }
+
+    #[staticmethod]
+    fn chinese() -> Self {
+        PyStopWords(StopWords::chinese())
+    }
+
+    #[staticmethod]
+    fn from_file(path: &str) -> PyResult<Self> {
+        Ok(PyStopWords(StopWords::from_file(path)?))
+    }
+
+    fn __or__(&self, other: &Self) -> Self {
+        PyStopWords(self.0.clone().union(other.0.clone()))
+    }
+
+    fn __contains__(&self, word: &str) -> bool {
+        self.0.contains(word)
+    }
+}
+
+/// Python类`Span`，包装[`Span`]，只读
+#[pyclass(name = "Span", frozen)]
+#[derive(Clone)]
+pub struct PySpan(Span);
+
+#[pymethods]
+impl PySpan {
+    #[getter]
+    fn start(&self) -> usize {
+        self.0.start
+    }
+
+    #[getter]
+    fn end(&self) -> usize {
+        self.0.end
+    }
+
+    #[getter]
+    fn char_start(&self) -> usize {
+        self.0.char_start
+    }
+
+    #[getter]
+    fn char_end(&self) -> usize {
+        self.0.char_end
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Span(start={}, end={}, char_start={}, char_end={})",
+            self.0.start, self.0.end, self.0.char_start, self.0.char_end
+        )
+    }
+}
+
+/// Python类`SearchHit`，包装[`SearchHit`]，只读
+///
+/// 可以传给`show_all`的`hits`参数高亮显示
+#[pyclass(name = "SearchHit", frozen)]
+#[derive(Clone)]
+pub struct PySearchHit(SearchHit);
+
+#[pymethods]
+impl PySearchHit {
+    #[getter]
+    fn index(&self) -> usize {
+        self.0.index
+    }
+
+    #[getter]
+    fn spans(&self) -> Vec<PySpan> {
+        self.0.spans.iter().copied().map(PySpan).collect()
+    }
+
+    fn __repr__(&self) -> String {
+        let spans: Vec<String> = self
+            .0
+            .spans
+            .iter()
+            .map(|s| format!("{}..{}", s.start, s.end))
+            .collect();
+        format!(
+            "SearchHit(index={}, spans=[{}])",
+            self.0.index,
+            spans.join(", ")
+        )
+    }
+}
+
+/// Python类`FuzzyHit`，包装[`FuzzyHit`]，只读
+#[pyclass(name = "FuzzyHit", frozen)]
+pub struct PyFuzzyHit(FuzzyHit);
+
+#[pymethods]
+impl PyFuzzyHit {
+    #[getter]
+    fn index(&self) -> usize {
+        self.0.index
+    }
+
+    #[getter]
+    fn distance(&self) -> usize {
+        self.0.distance
+    }
+
+    #[getter]
+    fn spans(&self) -> Vec<PySpan> {
+        self.0.spans.iter().copied().map(PySpan).collect()
+    }
+
+    /// 转换为`SearchHit`，以便传给`show_all`高亮显示
+    fn hit(&self) -> PySearchHit {
+        PySearchHit(self.0.clone().into())
+    }
+
+    fn __repr__(&self) -> String {
+        let spans: Vec<String> = self
+            .0
+            .spans
+            .iter()
+            .map(|s| format!("{}..{}", s.start, s.end))
+            .collect();
+        format!(
+            "FuzzyHit(index={}, distance={}, spans=[{}])",
+            self.0.index,
+            self.0.distance,
+            spans.join(", ")
+        )
+    }
+}
+
+/// Python类`Pattern`，包装预先编译的[`Pattern`]，只读
+///
+/// 可以代替字符串传给`regex_search`等方法，避免每次调用时重新编译
+#[pyclass(name = "Pattern", frozen)]
+pub struct PyPattern(Pattern);
+
+#[pymethods]
+impl PyPattern {
+    #[new]
+    #[pyo3(signature = (re, /, ignore_case=false, multi_line=false, unicode=true, size_limit=None))]
+    fn new(
+        re: &str,
+        ignore_case: bool,
+        multi_line: bool,
+        unicode: bool,
+        size_limit: Option<usize>,
+    ) -> PyResult<Self> {
+        let mut options = RegexOptions::new()
+            .case_insensitive(ignore_case)
+            .multi_line(multi_line)
+            .unicode(unicode);
+        if let Some(limit) = size_limit {
+            options = options.size_limit(limit);
+        }
+
+        Ok(PyPattern(Pattern::new(re, options)?))
+    }
+
+    #[getter]
+    fn pattern(&self) -> &str {
+        self.0.as_str()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Pattern({:?})", self.0.as_str())
+    }
+}
+
+/// `regex_search`等方法接受的正则表达式：字符串或[`PyPattern`]
+#[derive(FromPyObject)]
+enum RegexArg<'py> {
+    Pattern(PyRef<'py, PyPattern>),
+    Str(String),
+}
+
+impl RegexArg<'_> {
+    fn needle(&self) -> Needle<'_> {
+        match self {
+            RegexArg::Pattern(pattern) => Needle::Pattern(&pattern.0),
+            RegexArg::Str(re) => Needle::Regex(re),
+        }
+    }
+}
+
+/// Python类`ShiftEntry`，包装[`ShiftEntry`]，只读
+///
+/// `str()`得到移位序列本身，URL未匹配时为`None`
+#[pyclass(name = "ShiftEntry", frozen)]
+#[derive(Clone)]
+pub struct PyShiftEntry(ShiftEntry);
+
+#[pymethods]
+impl PyShiftEntry {
+    #[getter]
+    fn text(&self) -> &str {
+        &self.0.text
+    }
+
+    #[getter]
+    fn url(&self) -> Option<&str> {
+        self.0.url.as_deref()
+    }
+
+    #[getter]
+    fn source_line(&self) -> usize {
+        self.0.source_line
+    }
+
+    #[getter]
+    fn rotation_offset(&self) -> usize {
+        self.0.rotation_offset
+    }
+
+    #[getter]
+    fn keyword(&self) -> &str {
+        &self.0.keyword
+    }
+
+    fn __str__(&self) -> &str {
+        &self.0.text
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ShiftEntry(text={:?}, url={:?}, source_line={}, rotation_offset={}, keyword={:?})",
+            self.0.text, self.0.url, self.0.source_line, self.0.rotation_offset, self.0.keyword
+        )
+    }
+}
+
+/// Python类`Match`，包装[`Match`]，只读
+#[pyclass(name = "Match", frozen)]
+pub struct PyMatch(Match);
+
+#[pymethods]
+impl PyMatch {
+    #[getter]
+    fn index(&self) -> usize {
+        self.0.index
+    }
+
+    #[getter]
+    fn entry(&self) -> PyShiftEntry {
+        PyShiftEntry(self.0.entry.clone())
+    }
+
+    #[getter]
+    fn spans(&self) -> Vec<PySpan> {
+        self.0.spans.iter().copied().map(PySpan).collect()
+    }
+
+    fn __repr__(&self) -> String {
+        let spans: Vec<String> = self
+            .0
+            .spans
+            .iter()
+            .map(|s| format!("{}..{}", s.start, s.end))
+            .collect();
+        format!(
+            "Match(index={}, text={:?}, spans=[{}])",
+            self.0.index,
+            self.0.entry.text,
+            spans.join(", ")
+        )
+    }
+}
+
+fn wrap_entries(entries: Vec<ShiftEntry>) -> Vec<PyShiftEntry> {
+    entries.into_iter().map(PyShiftEntry).collect()
+}
+
+fn wrap_matches(matches: Vec<Match>) -> Vec<PyMatch> {
+    matches.into_iter().map(PyMatch).collect()
+}
+
+fn wrap_hits(hits: Vec<SearchHit>) -> Vec<PySearchHit> {
+    hits.into_iter().map(PySearchHit).collect()
+}
+
+fn wrap_fuzzy_hits(hits: Vec<FuzzyHit>) -> Vec<PyFuzzyHit> {
+    hits.into_iter().map(PyFuzzyHit).collect()
+}
+
+fn unwrap_hits(hits: Vec<PySearchHit>) -> Vec<SearchHit> {
+    hits.into_iter().map(|hit| hit.0).collect()
+}
+
+fn options(
+    whole_word: bool,
+    ignore_case: bool,
+    ignore_accents: bool,
+    cross_boundary: bool,
+) -> SearchOptions {
+    SearchOptions::new()
+        .whole_word(whole_word)
+        .ignore_case(ignore_case)
+        .ignore_accents(ignore_accents)
+        .cross_boundary(cross_boundary)
+}
+
+fn unwrap_spans(spans: Vec<PySpan>) -> Vec<Span> {
+    spans.into_iter().map(|span| span.0).collect()
+}
+
+/// Python类`RapidShifter`，包装[`RapidShifter`]
+#[pyclass(name = "RapidShifter")]
+pub struct PyRapidShifter(RapidShifter);
+
+#[pymethods]
+impl PyRapidShifter {
+    /// 参数`collation`为排序规则描述，参见[`collate::from_spec`]
+    /// 参数`stop_words`为停用词表[`PyStopWords`]
+    #[new]
+    #[pyo3(signature = (desc, /, collation="magic", stop_words=None, marker=None))]
+    fn new(
+        desc: String,
+        collation: &str,
+        stop_words: Option<PyStopWords>,
+        marker: Option<String>,
+    ) -> PyResult<Self> {
+        let collator = collate::from_spec(collation)?;
+        let mut shifter = RapidShifter::new(desc)
+            .with_collator(collator)
+            .with_stop_words(stop_words.map(|s| s.0).unwrap_or_default());
+        if let Some(marker) = marker {
+            shifter = shifter.with_marker(marker);
+        }
+        Ok(PyRapidShifter(shifter))
+    }
+
+    #[getter]
+    fn desc(&self) -> &str {
+        self.0.desc()
+    }
+
+    #[getter]
+    fn url(&self) -> Option<&str> {
+        self.0.url()
+    }
+
+    fn process(&mut self) {
+        self.0.process()
+    }
+
+    fn __getitem__(&mut self, index: usize) -> PyResult<String> {
+        Ok(self.0.get(index)?)
+    }
+
+    fn shifts(&mut self) -> Vec<String> {
+        self.0.shifts()
+    }
+
+    fn entry(&mut self, index: usize) -> PyResult<PyShiftEntry> {
+        Ok(PyShiftEntry(self.0.entry(index)?))
+    }
+
+    fn entries(&mut self) -> Vec<PyShiftEntry> {
+        wrap_entries(self.0.entries())
+    }
+
+    /// 参数`spans`为需要高亮的匹配位置
+    #[pyo3(signature = (line, spans=None))]
+    fn show_line(&mut self, line: usize, spans: Option<Vec<PySpan>>) -> PyResult<()> {
+        Ok(self
+            .0
+            .show_line_spans(line, &unwrap_spans(spans.unwrap_or_default()))?)
+    }
+
+    /// 参数`hits`为搜索结果时，仅展示匹配的序列并高亮匹配位置
+    #[pyo3(signature = (verbose=false, hits=None))]
+    fn show_all(&mut self, verbose: bool, hits: Option<Vec<PySearchHit>>) -> PyResult<()> {
+        match hits {
+            Some(hits) => self.0.show_hits(&unwrap_hits(hits), verbose)?,
+            None => self.0.show_all(verbose),
+        }
+        Ok(())
+    }
+
+    #[pyo3(signature = (patterns, all=false))]
+    fn search_many(
+        &mut self,
+        patterns: Vec<String>,
+        all: bool,
+    ) -> PyResult<HashMap<String, Vec<usize>>> {
+        Ok(self.0.search_many(&patterns, all)?)
+    }
+
+    #[pyo3(signature = (pat, all=false, whole_word=false, ignore_case=false, ignore_accents=false, cross_boundary=false))]
+    fn search(
+        &mut self,
+        pat: &str,
+        all: bool,
+        whole_word: bool,
+        ignore_case: bool,
+        ignore_accents: bool,
+        cross_boundary: bool,
+    ) -> PyResult<Option<Vec<usize>>> {
+        let options = options(whole_word, ignore_case, ignore_accents, cross_boundary);
+        Ok(self.0.search_with(pat, all, options)?)
+    }
+
+    #[pyo3(signature = (re, all=false, whole_word=false, ignore_case=false, ignore_accents=false, cross_boundary=false))]
+    fn regex_search(
+        &mut self,
+        re: RegexArg<'_>,
+        all: bool,
+        whole_word: bool,
+        ignore_case: bool,
+        ignore_accents: bool,
+        cross_boundary: bool,
+    ) -> PyResult<Option<Vec<usize>>> {
+        let options = options(whole_word, ignore_case, ignore_accents, cross_boundary);
+        Ok(self.0.search_with(re.needle(), all, options)?)
+    }
+
+    fn wildcard_search(&mut self, pattern: &str) -> Option<Vec<usize>> {
+        self.0.wildcard_search(pattern)
+    }
+
+    #[pyo3(signature = (term, max_distance=1))]
+    fn fuzzy_search(&mut self, term: &str, max_distance: usize) -> Vec<PyFuzzyHit> {
+        wrap_fuzzy_hits(self.0.fuzzy_search(term, max_distance))
+    }
+
+    fn query(&mut self, q: &str) -> PyResult<Option<Vec<usize>>> {
+        Ok(self.0.query(q)?)
+    }
+
+    #[pyo3(signature = (a, b, distance=1, ordered=false))]
+    fn near(&mut self, a: &str, b: &str, distance: usize, ordered: bool) -> Option<Vec<usize>> {
+        self.0.near(a, b, distance, ordered)
+    }
+
+    fn token_search(&mut self, pattern: &str) -> PyResult<Option<Vec<usize>>> {
+        Ok(self.0.token_search(pattern)?)
+    }
+
+    fn token_search_spans(&mut self, pattern: &str) -> PyResult<Vec<PySearchHit>> {
+        Ok(wrap_hits(self.0.token_search_spans(pattern)?))
+    }
+
+    #[pyo3(signature = (pat, all=false, whole_word=false, ignore_case=false, ignore_accents=false, cross_boundary=false))]
+    fn search_spans(
+        &mut self,
+        pat: &str,
+        all: bool,
+        whole_word: bool,
+        ignore_case: bool,
+        ignore_accents: bool,
+        cross_boundary: bool,
+    ) -> PyResult<Vec<PySearchHit>> {
+        let options = options(whole_word, ignore_case, ignore_accents, cross_boundary);
+        Ok(wrap_hits(self.0.search_spans(pat, all, options)?))
+    }
+
+    #[pyo3(signature = (re, all=false, whole_word=false, ignore_case=false, ignore_accents=false, cross_boundary=false))]
+    fn regex_search_spans(
+        &mut self,
+        re: RegexArg<'_>,
+        all: bool,
+        whole_word: bool,
+        ignore_case: bool,
+        ignore_accents: bool,
+        cross_boundary: bool,
+    ) -> PyResult<Vec<PySearchHit>> {
+        let options = options(whole_word, ignore_case, ignore_accents, cross_boundary);
+        Ok(wrap_hits(self.0.search_spans(re.needle(), all, options)?))
+    }
+
+    /// 返回Python的`range`对象
+    fn lookup<'py>(&mut self, py: Python<'py>, prefix: &str) -> PyResult<Bound<'py, PyRange>> {
+        let range = self.0.lookup(prefix)?;
+        PyRange::new(py, range.start as isize, range.end as isize)
+    }
+
+    #[pyo3(signature = (prefix, limit=10))]
+    fn suggest(&mut self, prefix: &str, limit: usize) -> PyResult<Vec<(String, usize)>> {
+        Ok(self.0.suggest(prefix, limit)?)
+    }
+
+    #[pyo3(signature = (pat, all=false))]
+    fn search_matches(&mut self, pat: &str, all: bool) -> PyResult<Vec<PyMatch>> {
+        Ok(wrap_matches(self.0.search_matches(
+            pat,
+            all,
+            SearchOptions::new(),
+        )?))
+    }
+
+    #[pyo3(signature = (re, all=false))]
+    fn regex_search_matches(&mut self, re: RegexArg<'_>, all: bool) -> PyResult<Vec<PyMatch>> {
+        Ok(wrap_matches(self.0.search_matches(
+            re.needle(),
+            all,
+            SearchOptions::new(),
+        )?))
+    }
+
+    #[allow(deprecated)]
+    fn qshifts(&mut self, py: Python<'_>) {
+        py.allow_threads(|| self.0.qshifts())
+    }
+}
+
+/// Python类`RapidShifterLines`，包装[`RapidShifterLines`]
+/// 耗时的处理过程与各种搜索（除二分查找的`lookup`与`suggest`外）会释放GIL，搜索在线程池中并行进行
+#[pyclass(name = "RapidShifterLines")]
+pub struct PyRapidShifterLines(RapidShifterLines);
+
+#[pymethods]
+impl PyRapidShifterLines {
+    /// 参数`collation`为排序规则描述，参见[`collate::from_spec`]
+    /// 参数`stop_words`为停用词表[`PyStopWords`]
+    /// 参数`threads`为并发处理的线程数，`0`表示使用全局线程池
+    #[new]
+    #[pyo3(signature = (item, collation="magic", stop_words=None, threads=0, marker=None))]
+    fn new(
+        item: Vec<String>,
+        collation: &str,
+        stop_words: Option<PyStopWords>,
+        threads: usize,
+        marker: Option<String>,
+    ) -> PyResult<Self> {
+        let collator = collate::from_spec(collation)?;
+        let mut shifter = RapidShifterLines::new(item)
+            .with_collator(collator)
+            .with_stop_words(stop_words.map(|s| s.0).unwrap_or_default())
+            .with_threads(threads)?;
+        if let Some(marker) = marker {
+            shifter = shifter.with_marker(marker);
+        }
+        Ok(PyRapidShifterLines(shifter))
+    }
+
+    #[getter]
+    fn threads(&self) -> usize {
+        self.0.threads()
+    }
+
+    fn process(&mut self, py: Python<'_>) {
+        py.allow_threads(|| self.0.process())
+    }
+
+    fn __getitem__(&mut self, py: Python<'_>, index: usize) -> PyResult<String> {
+        self.ensure(py);
+        Ok(self.0.get(index)?)
+    }
+
+    fn shifts(&mut self, py: Python<'_>) -> Vec<String> {
+        self.ensure(py);
+        self.0.shifts()
+    }
+
+    fn entry(&mut self, py: Python<'_>, index: usize) -> PyResult<PyShiftEntry> {
+        self.ensure(py);
+        Ok(PyShiftEntry(self.0.entry(index)?))
+    }
+
+    fn entries(&mut self, py: Python<'_>) -> Vec<PyShiftEntry> {
+        self.ensure(py);
+        wrap_entries(self.0.entries())
+    }
+
+    /// 参数`spans`为需要高亮的匹配位置
+    #[pyo3(signature = (line, spans=None))]
+    fn show_line(
+        &mut self,
+        py: Python<'_>,
+        line: usize,
+        spans: Option<Vec<PySpan>>,
+    ) -> PyResult<()> {
+        self.ensure(py);
+        Ok(self
+            .0
+            .show_line_spans(line, &unwrap_spans(spans.unwrap_or_default()))?)
+    }
+
+    /// 参数`hits`为搜索结果时，仅展示匹配的序列并高亮匹配位置
+    #[pyo3(signature = (verbose=false, hits=None))]
+    fn show_all(
+        &mut self,
+        py: Python<'_>,
+        verbose: bool,
+        hits: Option<Vec<PySearchHit>>,
+    ) -> PyResult<()> {
+        self.ensure(py);
+        match hits {
+            Some(hits) => self.0.show_hits(&unwrap_hits(hits), verbose)?,
+            None => self.0.show_all(verbose),
+        }
+        Ok(())
+    }
+
+    #[pyo3(signature = (patterns, all=false))]
+    fn search_many(
+        &mut self,
+        py: Python<'_>,
+        patterns: Vec<String>,
+        all: bool,
+    ) -> PyResult<HashMap<String, Vec<usize>>> {
+        self.ensure(py);
+        let shifter = &mut self.0;
+        Ok(py.allow_threads(|| shifter.search_many(&patterns, all))?)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (pat, all=false, whole_word=false, ignore_case=false, ignore_accents=false, cross_boundary=false))]
+    fn search(
+        &mut self,
+        py: Python<'_>,
+        pat: &str,
+        all: bool,
+        whole_word: bool,
+        ignore_case: bool,
+        ignore_accents: bool,
+        cross_boundary: bool,
+    ) -> PyResult<Option<Vec<usize>>> {
+        self.ensure(py);
+        let options = options(whole_word, ignore_case, ignore_accents, cross_boundary);
+        let shifter = &mut self.0;
+        Ok(py.allow_threads(|| shifter.search_with(pat, all, options))?)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (re, all=false, whole_word=false, ignore_case=false, ignore_accents=false, cross_boundary=false))]
+    fn regex_search(
+        &mut self,
+        py: Python<'_>,
+        re: RegexArg<'_>,
+        all: bool,
+        whole_word: bool,
+        ignore_case: bool,
+        ignore_accents: bool,
+        cross_boundary: bool,
+    ) -> PyResult<Option<Vec<usize>>> {
+        self.ensure(py);
+        let options = options(whole_word, ignore_case, ignore_accents, cross_boundary);
+        let shifter = &mut self.0;
+        let needle = re.needle();
+        Ok(py.allow_threads(|| shifter.search_with(needle, all, options))?)
+    }
+
+    fn wildcard_search(&mut self, py: Python<'_>, pattern: &str) -> Option<Vec<usize>> {
+        self.ensure(py);
+        let shifter = &mut self.0;
+        py.allow_threads(|| shifter.wildcard_search(pattern))
+    }
+
+    #[pyo3(signature = (term, max_distance=1))]
+    fn fuzzy_search(&mut self, py: Python<'_>, term: &str, max_distance: usize) -> Vec<PyFuzzyHit> {
+        self.ensure(py);
+        let shifter = &mut self.0;
+        wrap_fuzzy_hits(py.allow_threads(|| shifter.fuzzy_search(term, max_distance)))
+    }
+
+    fn query(&mut self, py: Python<'_>, q: &str) -> PyResult<Option<Vec<usize>>> {
+        self.ensure(py);
+        let shifter = &mut self.0;
+        Ok(py.allow_threads(|| shifter.query(q))?)
+    }
+
+    #[pyo3(signature = (a, b, distance=1, ordered=false))]
+    fn near(
+        &mut self,
+        py: Python<'_>,
+        a: &str,
+        b: &str,
+        distance: usize,
+        ordered: bool,
+    ) -> Option<Vec<usize>> {
+        self.ensure(py);
+        let shifter = &mut self.0;
+        py.allow_threads(|| shifter.near(a, b, distance, ordered))
+    }
+
+    fn token_search(&mut self, py: Python<'_>, pattern: &str) -> PyResult<Option<Vec<usize>>> {
+        self.ensure(py);
+        let shifter = &mut self.0;
+        Ok(py.allow_threads(|| shifter.token_search(pattern))?)
+    }
+
+    fn token_search_spans(&mut self, py: Python<'_>, pattern: &str) -> PyResult<Vec<PySearchHit>> {
+        self.ensure(py);
+        let shifter = &mut self.0;
+        Ok(wrap_hits(
+            py.allow_threads(|| shifter.token_search_spans(pattern))?,
+        ))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (pat, all=false, whole_word=false, ignore_case=false, ignore_accents=false, cross_boundary=false))]
+    fn search_spans(
+        &mut self,
+        py: Python<'_>,
+        pat: &str,
+        all: bool,
+        whole_word: bool,
+        ignore_case: bool,
+        ignore_accents: bool,
+        cross_boundary: bool,
+    ) -> PyResult<Vec<PySearchHit>> {
+        self.ensure(py);
+        let options = options(whole_word, ignore_case, ignore_accents, cross_boundary);
+        let shifter = &mut self.0;
+        Ok(wrap_hits(py.allow_threads(|| {
+            shifter.search_spans(pat, all, options)
+        })?))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (re, all=false, whole_word=false, ignore_case=false, ignore_accents=false, cross_boundary=false))]
+    fn regex_search_spans(
+        &mut self,
+        py: Python<'_>,
+        re: RegexArg<'_>,
+        all: bool,
+        whole_word: bool,
+        ignore_case: bool,
+        ignore_accents: bool,
+        cross_boundary: bool,
+    ) -> PyResult<Vec<PySearchHit>> {
+        self.ensure(py);
+        let options = options(whole_word, ignore_case, ignore_accents, cross_boundary);
+        let shifter = &mut self.0;
+        let needle = re.needle();
+        Ok(wrap_hits(py.allow_threads(|| {
+            shifter.search_spans(needle, all, options)
+        })?))
+    }
+
+    /// 返回Python的`range`对象
+    fn lookup<'py>(&mut self, py: Python<'py>, prefix: &str) -> PyResult<Bound<'py, PyRange>> {
+        self.ensure(py);
+        let range = self.0.lookup(prefix)?;
+        PyRange::new(py, range.start as isize, range.end as isize)
+    }
+
+    #[pyo3(signature = (prefix, limit=10))]
+    fn suggest(
+        &mut self,
+        py: Python<'_>,
+        prefix: &str,
+        limit: usize,
+    ) -> PyResult<Vec<(String, usize)>> {
+        self.ensure(py);
+        Ok(self.0.suggest(prefix, limit)?)
+    }
+
+    #[pyo3(signature = (pat, all=false))]
+    fn search_matches(&mut self, py: Python<'_>, pat: &str, all: bool) -> PyResult<Vec<PyMatch>> {
+        self.ensure(py);
+        let shifter = &mut self.0;
+        let matches =
+            py.allow_threads(|| shifter.search_matches(pat, all, SearchOptions::new()))?;
+        Ok(wrap_matches(matches))
+    }
+
+    #[pyo3(signature = (re, all=false))]
+    fn regex_search_matches(
+        &mut self,
+        py: Python<'_>,
+        re: RegexArg<'_>,
+        all: bool,
+    ) -> PyResult<Vec<PyMatch>> {
+        self.ensure(py);
+        let shifter = &mut self.0;
+        let needle = re.needle();
+        let matches =
+            py.allow_threads(|| shifter.search_matches(needle, all, SearchOptions::new()))?;
+        Ok(wrap_matches(matches))
+    }
+}
+
+impl PyRapidShifterLines {
+    /// 惰性处理时同样释放GIL
+    fn ensure(&mut self, py: Python<'_>) {
+        if !self.0.is_processed() {
+            self.process(py);
+        }
+    }
+}
+
+#[pymodule]
+fn rshifter(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyRapidShifter>()?;
+    m.add_class::<PyRapidShifterLines>()?;
+    m.add_class::<PyStopWords>()?;
+    m.add_class::<PySpan>()?;
+    m.add_class::<PySearchHit>()?;
+    m.add_class::<PyShiftEntry>()?;
+    m.add_class::<PyMatch>()?;
+    m.add_class::<PyFuzzyHit>()?;
+    m.add_class::<PyPattern>()?;
+    m.add("PyRegexError", m.py().get_type::<PyRegexError>())?;
+    m.add(
+        "PyRegexSyntaxError",
+        m.py().get_type::<PyRegexSyntaxError>(),
+    )?;
+    m.add(
+        "PyRegexCompiledTooBigError",
+        m.py().get_type::<PyRegexCompiledTooBigError>(),
+    )?;
+    m.add("PyQueryError", m.py().get_type::<PyQueryError>())?;
+    Ok(())
+}
@@ -0,0 +1,328 @@
+//! 查询语言模块
+//!
+//! 支持的语法：
+//! - 单词`Beijing`或带引号的短语`"Institute of"`，匹配包含该字符串的移位序列
+//! - 字段前缀`text:`（移位序列，默认）、`url:`（URL）与`keyword:`（开头单词，须完全相同），
+//!   其他`name:`开头的单词（如`http://a.b`）视为普通单词
+//! - 运算符`AND`、`OR`、`NOT`与括号，优先级`NOT` > `AND` > `OR`，相邻的条件之间默认为`AND`
+//!
+//! ```rust
+//! use rshifter::query::{Field, Query};
+//!
+//! let query: Query = "keyword:Beijing AND NOT (url:example OR \"of Technology\")".parse().unwrap();
+//! assert!(matches!(query, Query::And(..)));
+//! assert!("url:".parse::<Query>().is_err());
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{Error, Result};
+
+/// 查询条件作用的字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    /// 移位序列，包含该字符串即匹配
+    Text,
+    /// 所在行的URL，包含该字符串即匹配，没有URL时不匹配
+    Url,
+    /// 开头单词，与该字符串完全相同才匹配
+    Keyword,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "text" => Some(Field::Text),
+            "url" => Some(Field::Url),
+            "keyword" => Some(Field::Keyword),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Field::Text => "text",
+            Field::Url => "url",
+            Field::Keyword => "keyword",
+        })
+    }
+}
+
+/// 解析后的查询
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Query {
+    /// 单个条件
+    Term { field: Field, text: String },
+    /// 两个条件均满足
+    And(Box<Query>, Box<Query>),
+    /// 满足任意一个条件
+    Or(Box<Query>, Box<Query>),
+    /// 不满足条件
+    Not(Box<Query>),
+}
+
+impl Query {
+    /// 解析查询字符串，失败时返回[`Error::Query`]，携带出错位置（字符偏移）
+    pub fn parse(query: &str) -> Result<Self> {
+        let tokens = tokenize(query)?;
+        let mut parser = Parser {
+            query,
+            tokens,
+            pos: 0,
+        };
+
+        let res = parser.or()?;
+        // 解析结束后只可能剩下多余的右括号
+        match parser.tokens.get(parser.pos) {
+            None => Ok(res),
+            Some((_, at)) => Err(parser.error(*at, "unmatched `)`".to_string())),
+        }
+    }
+}
+
+impl FromStr for Query {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Query::parse(s)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+    Term(Field, String),
+}
+
+/// 切分为（记号，字符偏移）
+fn tokenize(query: &str) -> Result<Vec<(Token, usize)>> {
+    let error = |pos, msg: String| Error::Query {
+        query: query.to_string(),
+        pos,
+        msg,
+    };
+
+    let chars: Vec<char> = query.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let start = i;
+        match chars[i] {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => {
+                tokens.push((Token::LParen, start));
+                i += 1;
+                continue;
+            }
+            ')' => {
+                tokens.push((Token::RParen, start));
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        // 字段前缀，不是已知字段名时整个单词视为普通单词，如`http://a.b`
+        let mut field = None;
+        let name_len = chars[i..]
+            .iter()
+            .take_while(|c| c.is_ascii_alphabetic())
+            .count();
+        if chars.get(i + name_len) == Some(&':') {
+            let name: String = chars[i..i + name_len].iter().collect();
+            if let Some(known) = Field::from_name(&name) {
+                field = Some(known);
+                i += name_len + 1;
+            }
+        }
+
+        if chars.get(i) == Some(&'"') {
+            let (text, end) =
+                phrase(&chars, i + 1).ok_or_else(|| error(i, "unclosed quote".to_string()))?;
+            tokens.push((Token::Term(field.unwrap_or(Field::Text), text), start));
+            i = end;
+            continue;
+        }
+
+        let len = chars[i..]
+            .iter()
+            .take_while(|&&c| !c.is_whitespace() && !matches!(c, '(' | ')' | '"'))
+            .count();
+        let text: String = chars[i..i + len].iter().collect();
+        i += len;
+
+        // 带字段前缀时不视为运算符
+        let token = match (field, text.as_str()) {
+            (Some(field), "") => {
+                return Err(error(start, format!("missing term after `{field}:`")))
+            }
+            (None, "AND") => Token::And,
+            (None, "OR") => Token::Or,
+            (None, "NOT") => Token::Not,
+            (field, _) => Token::Term(field.unwrap_or(Field::Text), text),
+        };
+        tokens.push((token, start));
+    }
+
+    Ok(tokens)
+}
+
+/// 从`from`开始读取短语直到结束引号，支持`\"`与`\\`转义，返回短语与结束引号之后的位置
+fn phrase(chars: &[char], from: usize) -> Option<(String, usize)> {
+    let mut text = String::new();
+    let mut i = from;
+
+    loop {
+        match *chars.get(i)? {
+            '"' => return Some((text, i + 1)),
+            '\\' if matches!(chars.get(i + 1), Some('"' | '\\')) => {
+                text.push(chars[i + 1]);
+                i += 2;
+            }
+            c => {
+                text.push(c);
+                i += 1;
+            }
+        }
+    }
+}
+
+/// 递归下降解析
+struct Parser<'a> {
+    query: &'a str,
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, pos: usize, msg: String) -> Error {
+        Error::Query {
+            query: self.query.to_string(),
+            pos,
+            msg,
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    /// `or := and ("OR" and)*`
+    fn or(&mut self) -> Result<Query> {
+        let mut res = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            res = Query::Or(Box::new(res), Box::new(self.and()?));
+        }
+        Ok(res)
+    }
+
+    /// `and := not ("AND"? not)*`
+    fn and(&mut self) -> Result<Query> {
+        let mut res = self.not()?;
+        loop {
+            match self.peek() {
+                Some(Token::And) => self.pos += 1,
+                Some(Token::Term(..) | Token::Not | Token::LParen) => {}
+                _ => return Ok(res),
+            }
+            res = Query::And(Box::new(res), Box::new(self.not()?));
+        }
+    }
+
+    /// `not := "NOT" not | "(" or ")" | term`
+    fn not(&mut self) -> Result<Query> {
+        let Some((token, at)) = self.tokens.get(self.pos).cloned() else {
+            let end = self.query.chars().count();
+            return Err(self.error(end, "unexpected end of query".to_string()));
+        };
+        self.pos += 1;
+
+        match token {
+            Token::Not => Ok(Query::Not(Box::new(self.not()?))),
+            Token::LParen => {
+                let res = self.or()?;
+                if self.peek() != Some(&Token::RParen) {
+                    return Err(self.error(at, "unclosed `(`".to_string()));
+                }
+                self.pos += 1;
+                Ok(res)
+            }
+            Token::Term(field, text) => Ok(Query::Term { field, text }),
+            Token::RParen => Err(self.error(at, "unexpected `)`".to_string())),
+            Token::And | Token::Or => {
+                Err(self.error(at, "missing term before operator".to_string()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn term(field: Field, text: &str) -> Box<Query> {
+        Box::new(Query::Term {
+            field,
+            text: text.to_string(),
+        })
+    }
+
+    fn error_pos(query: &str) -> usize {
+        match Query::parse(query) {
+            Err(Error::Query { pos, .. }) => pos,
+            res => panic!("expected error, got {res:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Query::parse("a b OR NOT c").unwrap(),
+            Query::Or(
+                Box::new(Query::And(term(Field::Text, "a"), term(Field::Text, "b"))),
+                Box::new(Query::Not(term(Field::Text, "c"))),
+            )
+        );
+        assert_eq!(
+            Query::parse("keyword:a AND (url:\"x \\\"y\" OR text:z)").unwrap(),
+            Query::And(
+                term(Field::Keyword, "a"),
+                Box::new(Query::Or(term(Field::Url, "x \"y"), term(Field::Text, "z"))),
+            )
+        );
+        assert_eq!(
+            Query::parse("\"OR\" url:http://a.b").unwrap(),
+            Query::And(term(Field::Text, "OR"), term(Field::Url, "http://a.b"))
+        );
+        assert_eq!(
+            Query::parse("http://bit.edu.cn a:b").unwrap(),
+            Query::And(
+                term(Field::Text, "http://bit.edu.cn"),
+                term(Field::Text, "a:b")
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert_eq!(error_pos(""), 0);
+        assert_eq!(error_pos("a AND"), 5);
+        assert_eq!(error_pos("(a OR b"), 0);
+        assert_eq!(error_pos("a )"), 2);
+        assert_eq!(error_pos("OR a"), 0);
+        assert_eq!(error_pos("北京 \"a"), 3);
+        assert_eq!(error_pos("url: a"), 0);
+    }
+}
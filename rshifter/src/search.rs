@@ -0,0 +1,740 @@
+//! 搜索结果模块
+//!
+//! 提供匹配位置[`Span`]与搜索结果[`SearchHit`]，可以交给`show_hits`等方法高亮显示
+//! 以及附带移位序列来源的搜索结果[`Match`]、附带编辑距离的模糊搜索结果[`FuzzyHit`]
+//!
+//! 搜索选项[`SearchOptions`]设置全词匹配、忽略大小写与忽略重音
+//! 预先编译的正则表达式[`Pattern`]可以在多次搜索中复用，编译选项参见[`RegexOptions`]
+
+use std::ops::Range;
+use std::sync::OnceLock;
+
+use regex_automata::meta::{self, Regex};
+use regex_automata::util::syntax;
+use regex_automata::Input;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::error::{Error, Result};
+use crate::index::ShiftEntry;
+
+/// 搜索选项，默认区分大小写与重音，匹配任意子串
+///
+/// # Example:
+///
+/// ```rust
+/// use rshifter::search::SearchOptions;
+/// use rshifter::RapidShifter;
+///
+/// let mut shifter = RapidShifter::new(String::from("Université de Genève"));
+/// assert_eq!(shifter.search("universite", false), None);
+///
+/// let options = SearchOptions::new().ignore_case(true).ignore_accents(true);
+/// assert_eq!(shifter.search_with("universite", false, options).unwrap(), Some(vec![0, 1, 2]));
+///
+/// let options = SearchOptions::new().whole_word(true);
+/// assert_eq!(shifter.search_with("Gen", false, options).unwrap(), None);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchOptions {
+    whole_word: bool,
+    ignore_case: bool,
+    ignore_accents: bool,
+    cross_boundary: bool,
+}
+
+impl SearchOptions {
+    /// 创建默认选项
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置是否全词匹配，即匹配前后不能紧邻字母、数字或下划线
+    pub fn whole_word(mut self, whole: bool) -> Self {
+        self.whole_word = whole;
+        self
+    }
+
+    /// 设置是否忽略大小写
+    pub fn ignore_case(mut self, ignore: bool) -> Self {
+        self.ignore_case = ignore;
+        self
+    }
+
+    /// 设置是否忽略重音（即分解后的组合附加符号）
+    pub fn ignore_accents(mut self, ignore: bool) -> Self {
+        self.ignore_accents = ignore;
+        self
+    }
+
+    /// 设置匹配能否跨越原行末尾，默认不能，参见[`crate::Shift::wrap`]
+    ///
+    /// 例如`"aaa bbb ccc ddd"`的移位序列`"ddd aaa bbb ccc"`中，`"ddd aaa"`在原行中并不相邻，
+    /// 默认不会匹配
+    pub fn cross_boundary(mut self, cross: bool) -> Self {
+        self.cross_boundary = cross;
+        self
+    }
+
+    /// 是否只设置了是否跨越原行末尾，此时可以使用不折叠文本的快速路径
+    pub(crate) fn is_plain(&self) -> bool {
+        *self == Self::new().cross_boundary(self.cross_boundary)
+    }
+}
+
+/// 正则表达式编译选项，默认与[`regex::Regex::new`]一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegexOptions {
+    case_insensitive: bool,
+    multi_line: bool,
+    unicode: bool,
+    size_limit: Option<usize>,
+}
+
+impl Default for RegexOptions {
+    fn default() -> Self {
+        RegexOptions {
+            case_insensitive: false,
+            multi_line: false,
+            unicode: true,
+            size_limit: None,
+        }
+    }
+}
+
+impl RegexOptions {
+    /// 创建默认选项
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置是否忽略大小写，即`(?i)`
+    pub fn case_insensitive(mut self, yes: bool) -> Self {
+        self.case_insensitive = yes;
+        self
+    }
+
+    /// 设置`^`与`$`是否匹配每一行的开头与结尾，即`(?m)`
+    pub fn multi_line(mut self, yes: bool) -> Self {
+        self.multi_line = yes;
+        self
+    }
+
+    /// 设置是否启用Unicode，默认启用，即`(?u)`
+    pub fn unicode(mut self, yes: bool) -> Self {
+        self.unicode = yes;
+        self
+    }
+
+    /// 设置编译后的大小上限（字节），超出时返回[`Error::Regex`]
+    pub fn size_limit(mut self, bytes: usize) -> Self {
+        self.size_limit = Some(bytes);
+        self
+    }
+
+    /// 按选项编译，错误与[`regex::RegexBuilder::build`]一致
+    fn build(&self, source: &str) -> std::result::Result<Regex, regex::Error> {
+        let syntax = syntax::Config::new()
+            .case_insensitive(self.case_insensitive)
+            .multi_line(self.multi_line)
+            .unicode(self.unicode);
+        let config = match self.size_limit {
+            Some(limit) => meta::Config::new()
+                .nfa_size_limit(Some(limit))
+                .onepass_size_limit(Some(limit)),
+            None => meta::Config::new(),
+        };
+
+        Regex::builder()
+            .syntax(syntax)
+            .configure(config)
+            .build(source)
+            .map_err(|err| match err.size_limit() {
+                Some(limit) => regex::Error::CompiledTooBig(limit),
+                None => regex::Error::Syntax(err.to_string()),
+            })
+    }
+}
+
+/// 预先编译的正则表达式，可以在多次搜索中复用，不会每次重新编译
+///
+/// # Example:
+///
+/// ```rust
+/// use rshifter::search::{Pattern, RegexOptions, SearchOptions};
+/// use rshifter::RapidShifter;
+///
+/// let pattern = Pattern::new("^bei", RegexOptions::new().case_insensitive(true)).unwrap();
+/// let mut shifter = RapidShifter::new(String::from("Beijing Institute of Technology"));
+/// let found = shifter.search_with(&pattern, false, SearchOptions::new()).unwrap();
+/// assert_eq!(found, Some(vec![0]));
+///
+/// let options = RegexOptions::new().size_limit(16);
+/// assert!(Pattern::new(r"\w{100}", options).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    source: String,
+    regex: Regex,
+    options: RegexOptions,
+    /// 按搜索选项改写后的表达式，首次使用时编译，参见[`Pattern::variant`]
+    variants: [OnceLock<std::result::Result<Regex, regex::Error>>; 7],
+}
+
+impl Pattern {
+    /// 按选项编译正则表达式，失败时返回[`Error::Regex`]
+    pub fn new(re: &str, options: RegexOptions) -> Result<Self> {
+        let regex = options.build(re).map_err(|err| Error::Regex {
+            re: re.to_string(),
+            err,
+        })?;
+
+        Ok(Pattern {
+            source: re.to_string(),
+            regex,
+            options,
+            variants: Default::default(),
+        })
+    }
+
+    /// 原始表达式
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// 编译选项
+    pub fn options(&self) -> RegexOptions {
+        self.options
+    }
+
+    /// 按搜索选项改写后的表达式：全词匹配、忽略重音，或本身区分大小写而选项忽略大小写时需要改写
+    ///
+    /// 每种改写只编译一次并缓存，之后的搜索直接复用
+    fn variant(&self, options: &SearchOptions) -> Result<Regex> {
+        let ignore_case = options.ignore_case && !self.options.case_insensitive;
+        let slot = (options.whole_word as usize)
+            | (options.ignore_accents as usize) << 1
+            | (ignore_case as usize) << 2;
+        let Some(variant) = slot.checked_sub(1).map(|slot| &self.variants[slot]) else {
+            return Ok(self.regex.clone());
+        };
+
+        variant
+            .get_or_init(|| rewrite(&self.source, self.options, options))
+            .clone()
+            .map_err(|err| Error::Regex {
+                re: self.source.clone(),
+                err,
+            })
+    }
+}
+
+/// 搜索目标：字符串、正则表达式或预先编译的[`Pattern`]
+///
+/// `&str`转换为[`Needle::Text`]，`&Pattern`转换为[`Needle::Pattern`]
+#[derive(Debug, Clone, Copy)]
+pub enum Needle<'a> {
+    /// 字符串
+    Text(&'a str),
+    /// 正则表达式，每次搜索时编译
+    Regex(&'a str),
+    /// 预先编译的正则表达式，不会重新编译
+    Pattern(&'a Pattern),
+}
+
+impl<'a> From<&'a str> for Needle<'a> {
+    fn from(pat: &'a str) -> Self {
+        Needle::Text(pat)
+    }
+}
+
+impl<'a> From<&'a Pattern> for Needle<'a> {
+    fn from(pattern: &'a Pattern) -> Self {
+        Needle::Pattern(pattern)
+    }
+}
+
+/// 按选项折叠大小写与重音后的文本
+///
+/// `starts`记录折叠后每个字节所属字符在原文本中的起始位置，用于将匹配位置映射回原文本
+struct Folded<'a> {
+    source: &'a str,
+    text: String,
+    starts: Vec<usize>,
+}
+
+impl<'a> Folded<'a> {
+    fn new(source: &'a str, ignore_case: bool, ignore_accents: bool) -> Self {
+        let mut text = String::with_capacity(source.len());
+        let mut starts = Vec::with_capacity(source.len());
+
+        for (start, ch) in source.char_indices() {
+            let len = text.len();
+            for ch in fold_char(ch, ignore_case, ignore_accents) {
+                text.push(ch);
+            }
+            starts.resize(starts.len() + text.len() - len, start);
+        }
+
+        Folded {
+            source,
+            text,
+            starts,
+        }
+    }
+
+    /// 原文本字节位置`pos`（须为字符起始位置）对应的折叠后字节位置
+    fn position(&self, pos: usize) -> usize {
+        self.starts.partition_point(|&start| start < pos)
+    }
+
+    /// 折叠后的字节范围对应的原文本字节范围，部分覆盖的字符按整个字符计算
+    fn range(&self, range: Range<usize>) -> Range<usize> {
+        let start = self.starts.get(range.start).copied();
+        let start = start.unwrap_or(self.source.len());
+        if range.is_empty() {
+            return start..start;
+        }
+
+        let last = self.starts[range.end - 1];
+        let end = last + self.source[last..].chars().next().map_or(0, char::len_utf8);
+        start..end
+    }
+}
+
+/// 折叠单个字符：忽略重音时分解并去掉组合附加符号，忽略大小写时转为小写
+fn fold_char(ch: char, ignore_case: bool, ignore_accents: bool) -> Vec<char> {
+    let chars: Vec<char> = if ignore_accents {
+        std::iter::once(ch)
+            .nfd()
+            .filter(|&ch| !is_combining_mark(ch))
+            .collect()
+    } else {
+        vec![ch]
+    };
+
+    if ignore_case {
+        chars.into_iter().flat_map(char::to_lowercase).collect()
+    } else {
+        chars
+    }
+}
+
+fn fold(s: &str, ignore_case: bool, ignore_accents: bool) -> String {
+    s.chars()
+        .flat_map(|ch| fold_char(ch, ignore_case, ignore_accents))
+        .collect()
+}
+
+/// 是否为单词字符，与正则表达式中的`\w`一致
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_' || is_combining_mark(ch)
+}
+
+/// 按搜索选项查找字符串或正则表达式，给出原文本中的匹配位置
+pub(crate) struct Finder {
+    kind: Kind,
+    options: SearchOptions,
+}
+
+enum Kind {
+    /// 折叠后的字符串
+    Literal(String),
+    /// 编译后的正则表达式，大小写与全词匹配由正则表达式处理，文本只需折叠重音
+    Regex(Regex),
+}
+
+impl Finder {
+    /// 按搜索目标创建，正则表达式无效时返回[`Error::Regex`]
+    pub(crate) fn new(needle: Needle<'_>, options: SearchOptions) -> Result<Self> {
+        match needle {
+            Needle::Text(pat) => Ok(Self::literal(pat, options)),
+            Needle::Regex(re) => Self::regex(re, options),
+            Needle::Pattern(pattern) => Self::pattern(pattern, options),
+        }
+    }
+
+    /// 不折叠大小写/重音、不要求整词匹配的字符串，跨越原行末尾与否均可，此时无需拼接移位序列即可搜索
+    /// 不可跨越原行末尾时由调用方过滤跨越末尾的结果
+    pub(crate) fn plain_literal(&self) -> Option<&str> {
+        match &self.kind {
+            Kind::Literal(pat) if self.options.is_plain() => Some(pat),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn crosses_boundary(&self) -> bool {
+        self.options.cross_boundary
+    }
+
+    pub(crate) fn literal(pat: &str, options: SearchOptions) -> Self {
+        Finder {
+            kind: Kind::Literal(fold(pat, options.ignore_case, options.ignore_accents)),
+            options,
+        }
+    }
+
+    /// 编译正则表达式，失败时携带原始表达式返回[`Error::Regex`]
+    pub(crate) fn regex(re: &str, options: SearchOptions) -> Result<Self> {
+        Self::compile(re, RegexOptions::new(), options)
+    }
+
+    /// 使用预先编译的`pattern`，选项需要改写表达式时（全词匹配、忽略重音，
+    /// 或`pattern`区分大小写而选项忽略大小写）使用`pattern`缓存的改写结果
+    pub(crate) fn pattern(pattern: &Pattern, options: SearchOptions) -> Result<Self> {
+        Ok(Finder {
+            kind: Kind::Regex(pattern.variant(&options)?),
+            options,
+        })
+    }
+
+    fn compile(re: &str, regex_options: RegexOptions, options: SearchOptions) -> Result<Self> {
+        let compiled = rewrite(re, regex_options, &options).map_err(|err| Error::Regex {
+            re: re.to_string(),
+            err,
+        })?;
+
+        Ok(Finder {
+            kind: Kind::Regex(compiled),
+            options,
+        })
+    }
+
+    /// `text`中所有互不重叠的匹配位置
+    ///
+    /// `wrap`为原行末尾（即分隔空格）在`text`中的字节位置，参见[`crate::Shift::wrap`]，
+    /// 未设置[`SearchOptions::cross_boundary`]时分别在该位置之前与之后的两段中查找，
+    /// 因此不会遗漏完全位于某一段中的匹配；`^`、`$`与`\b`等断言仍参照整个`text`
+    pub(crate) fn find(&self, text: &str, wrap: Option<usize>) -> Vec<Range<usize>> {
+        self.find_n(text, wrap, usize::MAX)
+    }
+
+    /// 是否存在匹配，与[`Self::find`]一致
+    pub(crate) fn is_match(&self, text: &str, wrap: Option<usize>) -> bool {
+        let options = &self.options;
+        if wrap.is_none() || options.cross_boundary {
+            match &self.kind {
+                Kind::Regex(re) if !options.ignore_accents => return re.is_match(text),
+                Kind::Literal(pat) if *options == SearchOptions::new().cross_boundary(true) => {
+                    return text.contains(pat.as_str())
+                }
+                _ => {}
+            }
+        }
+
+        !self.find_n(text, wrap, 1).is_empty()
+    }
+
+    /// 最多`limit`个匹配位置
+    fn find_n(&self, text: &str, wrap: Option<usize>, limit: usize) -> Vec<Range<usize>> {
+        let options = &self.options;
+
+        let folded =
+            match self.kind {
+                Kind::Literal(_) if options.ignore_case || options.ignore_accents => Some(
+                    Folded::new(text, options.ignore_case, options.ignore_accents),
+                ),
+                Kind::Regex(_) if options.ignore_accents => Some(Folded::new(text, false, true)),
+                _ => None,
+            };
+        let haystack = folded.as_ref().map_or(text, |folded| folded.text.as_str());
+
+        let (tail, head) = match wrap.filter(|_| !options.cross_boundary) {
+            Some(wrap) => {
+                let wrap = folded.as_ref().map_or(wrap, |folded| folded.position(wrap));
+                (0..wrap, Some(wrap + 1..haystack.len()))
+            }
+            None => (0..haystack.len(), None),
+        };
+
+        let mut res = Vec::new();
+        for segment in std::iter::once(tail).chain(head) {
+            let mut from = segment.start;
+            while res.len() < limit {
+                let Some(found) = self.next(haystack, from..segment.end) else {
+                    break;
+                };
+                let whole = match &self.kind {
+                    Kind::Literal(_) if options.whole_word => is_whole(haystack, found.clone()),
+                    _ => true,
+                };
+
+                if whole {
+                    res.push(match &folded {
+                        Some(folded) => folded.range(found.clone()),
+                        None => found.clone(),
+                    });
+                }
+                from = if whole && !found.is_empty() {
+                    found.end
+                } else {
+                    let step = haystack[found.start..].chars().next();
+                    found.start + step.map_or(1, char::len_utf8)
+                };
+            }
+        }
+
+        res
+    }
+
+    /// `range`内的第一个匹配，正则表达式的断言可以看到`range`之外的文本
+    fn next(&self, haystack: &str, range: Range<usize>) -> Option<Range<usize>> {
+        if range.start > range.end {
+            return None;
+        }
+
+        match &self.kind {
+            Kind::Literal(pat) => {
+                let p = haystack[range.clone()].find(pat.as_str())?;
+                Some(range.start + p..range.start + p + pat.len())
+            }
+            Kind::Regex(re) => re
+                .find(Input::new(haystack).range(range))
+                .map(|m| m.range()),
+        }
+    }
+}
+
+/// 按搜索选项改写并编译正则表达式：忽略重音时折叠表达式中的重音，
+/// 忽略大小写时打开`case_insensitive`，全词匹配时在两侧加上单词边界
+fn rewrite(
+    re: &str,
+    regex_options: RegexOptions,
+    options: &SearchOptions,
+) -> std::result::Result<Regex, regex::Error> {
+    let source = if options.ignore_accents {
+        fold(re, false, true)
+    } else {
+        re.to_string()
+    };
+    let regex_options =
+        regex_options.case_insensitive(regex_options.case_insensitive || options.ignore_case);
+
+    let compiled = regex_options.build(&source)?;
+    if options.whole_word {
+        // 先单独编译原表达式，避免`a)(b`之类的表达式加上括号后变为合法
+        regex_options.build(&format!(r"\b{{start-half}}(?:{source})\b{{end-half}}"))
+    } else {
+        Ok(compiled)
+    }
+}
+
+/// 匹配前后是否均不紧邻单词字符
+fn is_whole(haystack: &str, range: Range<usize>) -> bool {
+    let before = haystack[..range.start].chars().next_back();
+    let after = haystack[range.end..].chars().next();
+    !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+}
+
+/// 匹配位置，同时给出字节范围与字符范围（均为左闭右开）
+///
+/// 范围相对于被搜索的字符串，即移位序列（搜索包括URL时为`"{shift} {url}"`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    /// 起始字节位置
+    pub start: usize,
+    /// 结束字节位置
+    pub end: usize,
+    /// 起始字符位置
+    pub char_start: usize,
+    /// 结束字符位置
+    pub char_end: usize,
+}
+
+impl Span {
+    /// 字节范围
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+/// 一个匹配的移位序列
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit {
+    /// 排序后的序号，与`get`的参数一致
+    pub index: usize,
+    /// 所有（互不重叠的）匹配位置
+    pub spans: Vec<Span>,
+}
+
+/// 附带移位序列及其来源的搜索结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    /// 排序后的序号，与`get`的参数一致
+    pub index: usize,
+    /// 匹配的移位序列
+    pub entry: ShiftEntry,
+    /// 所有（互不重叠的）匹配位置
+    pub spans: Vec<Span>,
+}
+
+/// 模糊搜索结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyHit {
+    /// 排序后的序号，与`get`的参数一致
+    pub index: usize,
+    /// 移位序列中的单词与搜索词之间的最小编辑距离
+    pub distance: usize,
+    /// 所有距离不超过上限的单词的位置
+    pub spans: Vec<Span>,
+}
+
+impl From<FuzzyHit> for SearchHit {
+    fn from(hit: FuzzyHit) -> Self {
+        SearchHit {
+            index: hit.index,
+            spans: hit.spans,
+        }
+    }
+}
+
+/// 由字节范围计算匹配位置，`ranges`须按起始位置递增且互不重叠
+pub(crate) fn spans(text: &str, ranges: impl Iterator<Item = Range<usize>>) -> Vec<Span> {
+    let (mut byte, mut chars) = (0, 0);
+
+    ranges
+        .map(|range| {
+            chars += text[byte..range.start].chars().count();
+            let char_start = chars;
+            chars += text[range.clone()].chars().count();
+            byte = range.end;
+
+            Span {
+                start: range.start,
+                end: range.end,
+                char_start,
+                char_end: chars,
+            }
+        })
+        .collect()
+}
+
+/// 截取落在`[offset, offset + len)`内的匹配部分，返回相对于`offset`的字节范围
+/// 空匹配没有可以高亮的内容，会被丢弃
+pub(crate) fn clip(spans: &[Span], offset: usize, len: usize) -> Vec<Range<usize>> {
+    spans
+        .iter()
+        .filter_map(|span| {
+            let start = span.start.max(offset);
+            let end = span.end.min(offset + len);
+            (start < end).then(|| start - offset..end - offset)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_spans() {
+        let text = "北京 ab 北京";
+        let ranges = text.match_indices("北京").map(|(i, s)| i..i + s.len());
+        let spans = spans(text, ranges);
+
+        assert_eq!(spans.len(), 2);
+        assert_eq!((spans[0].char_start, spans[0].char_end), (0, 2));
+        assert_eq!((spans[1].char_start, spans[1].char_end), (6, 8));
+        assert_eq!(&text[spans[1].range()], "北京");
+
+        assert_eq!(clip(&spans, 0, 7), vec![(0..6)]);
+        assert_eq!(clip(&spans, 8, 6), vec![(2..6)]);
+    }
+
+    #[test]
+    fn test_finder() {
+        let options = SearchOptions::new();
+        let text = "uni University universe Zürich ZURICH";
+
+        let whole = Finder::literal("uni", options.whole_word(true));
+        assert_eq!(whole.find(text, None), vec![(0..3)]);
+        let nocase = Finder::literal("uni", options.ignore_case(true));
+        assert_eq!(nocase.find(text, None), vec![(0..3), (4..7), (15..18)]);
+
+        let folded = Finder::literal("zurich", options.ignore_case(true).ignore_accents(true));
+        let ranges = folded.find(text, None);
+        assert_eq!(ranges, vec![(24..31), (32..38)]);
+        assert_eq!(&text[ranges[0].clone()], "Zürich");
+
+        let re = Finder::regex("^u.i$|Z.rich", options.whole_word(true)).unwrap();
+        assert_eq!(re.find(text, None), vec![(24..31)]);
+        let re = Finder::regex("zu", options.ignore_case(true).ignore_accents(true)).unwrap();
+        assert_eq!(re.find(text, None), vec![(24..27), (32..34)]);
+        assert!(Finder::regex("a)(b", options.whole_word(true)).is_err());
+    }
+
+    #[test]
+    fn test_pattern() {
+        let options = SearchOptions::new();
+        let text = "Beijing\nbeijing";
+
+        let pattern = Pattern::new("^bei", RegexOptions::new().multi_line(true)).unwrap();
+        assert_eq!(
+            Finder::pattern(&pattern, options).unwrap().find(text, None),
+            vec![(8..11)]
+        );
+        let finder = Finder::pattern(&pattern, options.ignore_case(true)).unwrap();
+        assert_eq!(finder.find(text, None), vec![(0..3), (8..11)]);
+        let finder = Finder::pattern(&pattern, options.whole_word(true)).unwrap();
+        assert!(finder.find(text, None).is_empty());
+
+        // 改写后的表达式只编译一次
+        let cached = |pattern: &Pattern| {
+            pattern
+                .variants
+                .iter()
+                .filter(|v| v.get().is_some())
+                .count()
+        };
+        assert_eq!(cached(&pattern), 2);
+        Finder::pattern(&pattern, options.ignore_case(true)).unwrap();
+        assert_eq!(cached(&pattern), 2);
+        Finder::pattern(&pattern, options).unwrap();
+        assert_eq!(cached(&pattern), 2);
+        let accents = Pattern::new("^geneve", RegexOptions::new()).unwrap();
+        let finder = Finder::pattern(&accents, options.ignore_accents(true)).unwrap();
+        assert!(finder.find("Genève genève", None).is_empty());
+        let finder =
+            Finder::pattern(&accents, options.ignore_accents(true).ignore_case(true)).unwrap();
+        assert_eq!(finder.find("Genève genève", None), vec![(0..7)]);
+
+        let ascii = Pattern::new(r"(?-u:\w)+", RegexOptions::new().unicode(false)).unwrap();
+        assert_eq!(
+            Finder::pattern(&ascii, options)
+                .unwrap()
+                .find("Zürich", None),
+            vec![(0..1), (3..7)]
+        );
+        assert!(matches!(
+            Pattern::new(r"\w+", RegexOptions::new().size_limit(10)),
+            Err(Error::Regex {
+                err: regex::Error::CompiledTooBig(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_finder_wrap() {
+        // "ddd aaa bbb ccc"，原行末尾位于第3个字节
+        let text = "ddd aaa bbb ccc";
+        let options = SearchOptions::new();
+
+        let finder = Finder::literal("ddd aaa", options);
+        assert!(!finder.is_match(text, Some(3)));
+        assert!(finder.is_match(text, None));
+        let finder = Finder::literal("ddd aaa", options.cross_boundary(true));
+        assert!(finder.is_match(text, Some(3)));
+
+        let re = Finder::regex(r"\w+ \w+", options).unwrap();
+        assert_eq!(re.find(text, Some(3)), vec![(4..11)]);
+        assert_eq!(re.find(text, None), vec![(0..7), (8..15)]);
+
+        // 跨越原行末尾的最长匹配被排除后，同一起点上较短的匹配仍应找到
+        let re = Finder::regex("x.*c", options).unwrap();
+        assert_eq!(re.find("x c a c", Some(3)), vec![(0..3)]);
+        assert!(re.is_match("x c a c", Some(3)));
+        let re = Finder::regex("^a", options).unwrap();
+        assert!(!re.is_match("x c a c", Some(3)));
+    }
+}
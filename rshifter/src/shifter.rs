@@ -1,773 +1,374 @@
 //! 循环移位器[`RapidShifter`]与[`RapidShifterLines`]的实现模块
 //! 提供移位产生/搜索等一系列功能
+//!
+//! 移位序列以标记的形式存储在[`ShiftIndex`]中，仅在需要时才拼接为字符串
 
-use std::arch::asm;
-use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::iter::zip;
-use std::sync::{Arc, Mutex};
-use std::thread;
+use std::ops::Range;
 
-use pyo3::create_exception;
-use pyo3::exceptions;
-use pyo3::prelude::*;
 use regex::Regex;
 
-use crate::color::Color;
-use crate::Direction;
-use crate::RapidShifterIter;
+use crate::collate::Collator;
+use crate::cql::TokenPattern;
+use crate::error::Result;
+use crate::index::{Line, Shift, ShiftEntry, ShiftIndex};
+use crate::query::Query;
+use crate::search::{FuzzyHit, Match, Needle, SearchHit, SearchOptions, Span};
+use crate::stopwords::StopWords;
 
-// TODO: 增加搜索高亮显示功能
-
-type Shift = Arc<Mutex<Vec<String>>>;
-
-/// 若未匹配到URL，使用该字符串代替
-const NONE: &str = "<None>";
+/// 匹配行尾URL的正则表达式
 const URL_RE: &str = r"^(https?|ftp)://[^\s/$.?#].[^\s]*$";
 
-create_exception!(rshifter, PyRegexSyntaxError, pyo3::exceptions::PyException);
-create_exception!(
-    rshifter,
-    PyRegexCompiledTooBigError,
-    pyo3::exceptions::PyException
-);
-
-macro_rules! lazy_check {
-    ($param: expr, $func: expr) => {
-        if $param.is_none() {
-            $func;
-        }
-    };
-}
+/// 两种移位器共有的方法，均委托给字段`index`，文档只在此处编写
+macro_rules! shared_methods {
+    ($shifter: ident) => {
+        impl $shifter {
+            /// 设置排序规则，已产生的移位序列会被丢弃
+            pub fn with_collator(mut self, collator: Box<dyn Collator>) -> Self {
+                self.index.set_collator(collator);
+                self
+            }
 
-/// 移位序列排序函数
-/// 可以按"a > A > b > B > c > C ..."顺序进行排序
-/// 原理参见Python版本注释
-#[inline(always)]
-fn magic(x: &str, y: &str) -> Ordering {
-    let mut iter = zip(x.as_bytes(), y.as_bytes());
-    let (mut key_x, mut key_y): (u8, u8);
-    loop {
-        if let Some((dx, dy)) = iter.next() {
-            // HACK: That works, see also in python version
-            // SAFETY: only read dx & dy
-            unsafe {
-                asm!(
-                    "mov r10b, BYTE PTR [r8]",
-                    "mov r11b, BYTE PTR [r9]",
-                    "rol r10b, 3", "rol r11b, 3", "xor r10b, 0x1", "xor r11b, 0x1",
-                    in("r8") dx,
-                    in("r9") dy,
-                    out("r10b") key_x,
-                    out("r11b") key_y,
-                );
+            /// 设置停用词表，以停用词开头的移位序列不会被产生
+            /// 停用词仍然保留在其它移位序列中，已产生的移位序列会被丢弃
+            pub fn with_stop_words(mut self, stop_words: StopWords) -> Self {
+                self.index.set_stop_words(stop_words);
+                self
             }
 
-            match key_x.cmp(&key_y) {
-                Ordering::Less => break Ordering::Less,
-                Ordering::Greater => break Ordering::Greater,
-                Ordering::Equal => continue,
+            /// 设置展示时标记原行末尾的字符串，如`"/"`，展示为`"ddd / aaa bbb ccc"`
+            ///
+            /// 只影响`show_*`方法的输出，`get`与`shifts`等返回的移位序列不变
+            pub fn with_marker(mut self, marker: impl Into<String>) -> Self {
+                self.index.set_marker(Some(marker.into()));
+                self
             }
-        } else {
-            break y.len().cmp(&x.len());
-        };
-    }
-}
 
-/// 快速移位序列结构体`RapidShifter`
-/// 存储描述`desc`，URL`url`与排序后移位序列`shifts`
-///
-/// 使用[`lazy_check`]宏进行惰性处理，在需要时才会进行移位排序，产生开销
-/// 也可以提前调用方法[`RapidShifter::process`]来产生所有移位序列
-#[pyclass]
-#[derive(Debug)]
-pub struct RapidShifter {
-    #[pyo3(get)]
-    desc: String,
-    #[pyo3(get)]
-    url: Option<String>,
+            /// 得到特定的循环序列，格式为`"{shift} {url}"`
+            pub fn get(&mut self, index: usize) -> Result<String> {
+                self.index.get(index, "index")
+            }
 
-    shifts: Option<Vec<String>>,
-}
+            /// 得到特定的循环序列，不拼接字符串
+            pub fn shift(&mut self, index: usize) -> Option<Shift<'_>> {
+                self.index.shift(index)
+            }
 
-// TODO: use mark-based sort and optimize search
-// add `fn search_show()`
+            /// 得到特定的循环序列及其来源（所在行、偏移、关键词与URL）
+            pub fn entry(&mut self, index: usize) -> Result<ShiftEntry> {
+                self.index.entry(index)
+            }
 
-#[pymethods]
-impl RapidShifter {
-    /// 初始化函数
-    /// 参数`desc`为给定字符串
-    /// 使用正则表达式尝试匹配URL，若匹配失败则设置URL为[`None`]
-    /// 仅匹配最后一个单词块（以' '作为分隔符）
-    #[new]
-    #[pyo3(signature = (desc, /))]
-    pub fn new(desc: String) -> Self {
-        let (string, url) = desc.trim().rsplit_once(' ').unwrap_or_default();
+            /// 得到所有的循环序列
+            pub fn shifts(&mut self) -> Vec<String> {
+                self.index.shifts()
+            }
 
-        let re = Regex::new(r"^(https?|ftp)://[^\s/$.?#].[^\s]*$").unwrap();
+            /// 得到所有的循环序列及其来源
+            pub fn entries(&mut self) -> Vec<ShiftEntry> {
+                self.index.entries()
+            }
 
-        if re.is_match(url) {
-            return RapidShifter {
-                desc: string.to_string(),
-                shifts: None,
-                url: Some(url.to_string()),
-            };
-        };
+            /// 展示特定列
+            pub fn show_line(&mut self, line: usize) -> Result<()> {
+                self.index.show_line(line)
+            }
 
-        RapidShifter {
-            desc,
-            shifts: None,
-            url: None,
-        }
-    }
+            /// 展示特定列，并高亮其中的匹配位置`spans`，参见[`SearchHit`]
+            pub fn show_line_spans(&mut self, line: usize, spans: &[Span]) -> Result<()> {
+                self.index.show_line_spans(line, spans)
+            }
 
-    /// 移位排序处理函数
-    /// 调用仅会生成所有移位序列并排序，不会返回值
-    /// 需要移位序列参见函数[`RapidShifter::__getitem__`]或[`RapidShifter::shifts`]
-    #[inline]
-    pub fn process(&mut self) {
-        let mut shifts = self.iter().collect::<Vec<String>>();
-        shifts.sort_unstable_by(|x, y| magic(x, y));
-        self.shifts = Some(shifts);
-    }
+            /// 展示所有列
+            /// 参数`verbose`为是否详细展示
+            pub fn show_all(&mut self, verbose: bool) {
+                self.index.show_all(verbose)
+            }
 
-    /// 得到特定的循环序列
-    pub fn __getitem__(&mut self, index: usize) -> PyResult<String> {
-        lazy_check!(self.shifts, self.process());
+            /// 展示搜索结果`hits`，高亮其中的匹配位置
+            /// 参数`verbose`为是否详细展示，序号或匹配位置不属于本实例时返回[`Error::OutOfRange`](crate::Error::OutOfRange)
+            pub fn show_hits(&mut self, hits: &[SearchHit], verbose: bool) -> Result<()> {
+                self.index.show_hits(hits, verbose)
+            }
 
-        if index >= self.shifts.as_ref().unwrap().len() {
-            return Err(exceptions::PyIndexError::new_err(
-                "arg `index` is out of range",
-            ));
-        }
+            /// 搜索特定字符串
+            /// 参数`all`设置搜索内容是否包括URL
+            ///
+            /// 对每一行只查找一次，直接计算出包含匹配的移位序列，
+            /// 耗时与原行长度成线性关系，而不是与所有移位序列的总长度
+            ///
+            /// 匹配不能跨越原行末尾：`"ddd aaa"`不会匹配`"aaa bbb ccc ddd"`的任何移位序列，
+            /// 需要时参见[`SearchOptions::cross_boundary`]
+            pub fn search(&mut self, pat: &str, all: bool) -> Option<Vec<usize>> {
+                self.index.search(pat, all)
+            }
 
-        let res = format!(
-            "{} {}",
-            &self.shifts.as_ref().unwrap()[index],
-            &self.url.as_ref().map_or(NONE, |s| s.as_str())
-        );
+            /// 通过正则表达式搜索特定字符串
+            /// 参数`all`设置搜索内容是否包括URL，匹配同样不能跨越原行末尾
+            pub fn regex_search(&mut self, re: &str, all: bool) -> Result<Option<Vec<usize>>> {
+                self.index.regex_search(re, all)
+            }
 
-        Ok(res)
-    }
+            /// 按选项搜索字符串、正则表达式或预先编译的[`Pattern`](crate::search::Pattern)，参见[`Needle`]
+            /// 与[`SearchOptions`]（全词匹配、忽略大小写与重音等）
+            ///
+            /// 默认选项的字符串与[`Self::search`]相同，不会拼接移位序列；
+            /// 其余情况拼接每个移位序列后并行匹配
+            /// 正则表达式无效时返回[`Error::Regex`](crate::Error::Regex)
+            pub fn search_with<'a>(
+                &mut self,
+                needle: impl Into<Needle<'a>>,
+                all: bool,
+                options: SearchOptions,
+            ) -> Result<Option<Vec<usize>>> {
+                self.index.search_with(needle.into(), all, options)
+            }
 
-    /// 得到所有的循环序列
-    pub fn shifts(&mut self) -> &Vec<String> {
-        lazy_check!(self.shifts, self.process());
+            /// 同[`Self::search_with`]，并给出每个匹配序列中所有匹配的字节/字符位置
+            ///
+            /// `all`为真时位置相对于`"{shift} {url}"`；忽略大小写或重音时同样相对于原移位序列
+            pub fn search_spans<'a>(
+                &mut self,
+                needle: impl Into<Needle<'a>>,
+                all: bool,
+                options: SearchOptions,
+            ) -> Result<Vec<SearchHit>> {
+                self.index.search_spans(needle.into(), all, options)
+            }
 
-        self.shifts.as_ref().unwrap()
-    }
+            /// 同[`Self::search_spans`]，并附带每个匹配序列及其来源
+            pub fn search_matches<'a>(
+                &mut self,
+                needle: impl Into<Needle<'a>>,
+                all: bool,
+                options: SearchOptions,
+            ) -> Result<Vec<Match>> {
+                let hits = self.index.search_spans(needle.into(), all, options)?;
+                Ok(self.index.matches(hits))
+            }
 
-    /// 展示特定列
-    pub fn show_line(&mut self, line: usize) -> PyResult<()> {
-        lazy_check!(self.shifts, self.process());
+            /// 同时搜索多个字符串，返回每个字符串到匹配序号的映射，不含没有匹配的字符串
+            ///
+            /// 所有字符串编译为一个Aho-Corasick自动机，只扫描一遍移位序列，
+            /// 适合大量关键词，避免逐个调用[`Self::search`]；匹配同样不能跨越原行末尾
+            pub fn search_many<S: AsRef<str>>(
+                &mut self,
+                patterns: &[S],
+                all: bool,
+            ) -> Result<HashMap<String, Vec<usize>>> {
+                self.index.search_many(patterns, all)
+            }
 
-        if line > self.shifts.as_ref().unwrap().len() {
-            return Err(exceptions::PyIndexError::new_err(
-                "arg `line` is out of range",
-            ));
-        }
+            /// 通配符搜索，返回开头单词与`pattern`匹配的移位序列，如`tech*`、`*ology`、`be*ng`
+            ///
+            /// 基于所有单词的轮排索引(permuterm)，首次调用时建立，之后的查询无需扫描移位序列
+            pub fn wildcard_search(&mut self, pattern: &str) -> Option<Vec<usize>> {
+                self.index.wildcard_search(pattern)
+            }
 
-        let line = &self.shifts.as_ref().unwrap()[line];
+            /// 模糊搜索，返回包含与`term`编辑距离不超过`max_distance`的单词的移位序列，
+            /// 如`"Universty"`可以找到含有`"University"`的序列
+            ///
+            /// 距离为Damerau-Levenshtein距离，只在去重后的词表上计算，结果按距离升序排列
+            pub fn fuzzy_search(&mut self, term: &str, max_distance: usize) -> Vec<FuzzyHit> {
+                self.index.fuzzy_search(term, max_distance)
+            }
 
-        let url = self.url.as_ref().map_or(NONE, |s| s.as_str());
+            /// 按查询语言搜索，如`keyword:Beijing AND NOT url:example`，语法参见[`crate::query`]
+            ///
+            /// 查询无法解析时返回[`Error::Query`](crate::Error::Query)
+            pub fn query(&mut self, query: &str) -> Result<Option<Vec<usize>>> {
+                let query = Query::parse(query)?;
+                Ok(self.index.query(&query))
+            }
 
-        println!("{line} {url}");
-        Ok(())
-    }
+            /// 邻近搜索，返回原行中单词`a`与`b`相距不超过`distance`个单词的移位序列，
+            /// 如`near("Institute", "Technology", 3, false)`
+            ///
+            /// 单词须完全相同，`ordered`为真时`a`须在`b`之前；两者之间被原行末尾截断的移位序列不匹配
+            pub fn near(
+                &mut self,
+                a: &str,
+                b: &str,
+                distance: usize,
+                ordered: bool,
+            ) -> Option<Vec<usize>> {
+                self.index.near(a, b, distance, ordered)
+            }
 
-    /// 展示所有列
-    /// 参数`verbose`为是否详细展示
-    #[pyo3(signature = (verbose=false))]
-    pub fn show_all(&mut self, verbose: bool) {
-        lazy_check!(self.shifts, self.process());
+            /// 按单词级模式搜索，如`[word="Bei.*"] [] [word="(?i)technology"]`，语法参见[`crate::cql`]
+            ///
+            /// 模式在每一行已切分的单词序列上匹配，不会重新切分移位序列；匹配不能跨越原行末尾
+            /// 模式无法解析时返回[`Error::Query`](crate::Error::Query)或[`Error::Regex`](crate::Error::Regex)
+            pub fn token_search(&mut self, pattern: &str) -> Result<Option<Vec<usize>>> {
+                let indices: Vec<usize> = self
+                    .token_search_spans(pattern)?
+                    .into_iter()
+                    .map(|hit| hit.index)
+                    .collect();
+                Ok((!indices.is_empty()).then_some(indices))
+            }
 
-        let shifts = self.shifts.as_ref().unwrap();
+            /// 按单词级模式搜索，给出每个匹配序列中所有匹配的字节/字符位置
+            pub fn token_search_spans(&mut self, pattern: &str) -> Result<Vec<SearchHit>> {
+                let pattern = TokenPattern::parse(pattern)?;
+                Ok(self.index.token_search(&pattern))
+            }
 
-        let url = self.url.as_ref().map_or(NONE, |s| s.as_str());
+            /// 查找以`prefix`开头的移位序列，返回其在排序后的连续序号范围
+            ///
+            /// 使用当前排序规则二分查找，无需扫描，耗时为O(log n)
+            /// 前缀按排序规则比较，参见[`Collator::compare_prefix`](crate::collate::Collator::compare_prefix)，
+            /// 例如忽略大小写排序时，`"beijing"`同样会找到以`"Beijing"`开头的序列
+            /// 自然排序时前缀中的数字按数值匹配完整的数字片段，`"Chapter 1"`会找到`"Chapter 01"`，
+            /// 但不会找到`"Chapter 12"`，因为后者与`"Chapter 2"`等交错排列，无法二分查找
+            /// 排序规则不支持时返回[`Error::Unsupported`](crate::Error::Unsupported)
+            pub fn lookup(&mut self, prefix: &str) -> Result<Range<usize>> {
+                self.index.lookup(prefix)
+            }
 
-        for (num, line) in shifts.iter().enumerate() {
-            if verbose {
-                print!("{}", Color::Purple.color(&format!("[{:0>2}] ", num + 1)));
-                println!("{} {}", Color::Blue.color(line), Color::Yellow.color(url));
-            } else {
-                println!("{line} {url}");
-            };
+            /// 输入提示：给出以`prefix`开头的补全及其出现次数
+            ///
+            /// 每个补全将`prefix`延伸到当前单词末尾，如`"Bei"`补全为`"Beijing"`，
+            /// `"Beijing "`补全为`"Beijing Institute"`，基于[`Self::lookup`]，不会扫描所有移位序列
+            /// 按次数从多到少排列，最多返回`limit`个
+            pub fn suggest(&mut self, prefix: &str, limit: usize) -> Result<Vec<(String, usize)>> {
+                self.index.suggest(prefix, limit)
+            }
         }
-    }
+    };
+}
 
-    /// 搜索特定字符串
-    /// 参数`all`设置搜索内容是否包括URL
-    #[pyo3(signature = (pat, all=false))]
-    pub fn search(&mut self, pat: String, all: bool) -> Option<Vec<usize>> {
-        lazy_check!(self.shifts, self.process());
+/// 快速移位序列结构体`RapidShifter`
+/// 存储描述`desc`，URL`url`与排序后的移位序列标记
+///
+/// 使用`lazy_check`宏进行惰性处理，在需要时才会进行移位排序，产生开销
+/// 也可以提前调用方法[`RapidShifter::process`]来产生所有移位序列
+///
+/// 排序规则默认为[`Magic`](crate::collate::Magic)，可以通过[`RapidShifter::with_collator`]修改
+/// 通过[`RapidShifter::with_stop_words`]可以丢弃以停用词开头的移位序列
+#[derive(Debug)]
+pub struct RapidShifter {
+    desc: String,
+    url: Option<String>,
 
-        let shifts = self.shifts.as_ref().unwrap();
-        let desc_len = self.desc.len();
+    index: ShiftIndex,
+}
 
-        if (all && self.url.as_ref().unwrap_or(&String::new()).contains(&pat))
-            || (!pat.contains(" ") && self.desc.contains(&pat))
-        {
-            return Some((0..shifts.len()).collect());
-        }
+impl RapidShifter {
+    /// 初始化函数
+    /// 参数`desc`为给定字符串
+    /// 使用正则表达式尝试匹配URL，若匹配失败则设置URL为[`None`]
+    /// 仅匹配最后一个单词块（以' '作为分隔符）
+    pub fn new(desc: String) -> Self {
+        let (string, url) = desc.trim().rsplit_once(' ').unwrap_or_default();
 
-        let pat_len = pat.len();
+        let re = Regex::new(URL_RE).unwrap();
 
-        let url = if all {
-            &format!(" {}", self.url.as_ref().map_or(NONE, |s| s.as_str()))
+        let (desc, url) = if re.is_match(url) {
+            (string.to_string(), Some(url.to_string()))
         } else {
-            ""
+            (desc, None)
         };
 
-        if (!all && desc_len <= pat_len) || (all && desc_len + url.len() < pat_len) {
-            return None;
-        }
+        let index = ShiftIndex::new(vec![Line::new(&desc, url.clone())]);
 
-        let mut res: Vec<usize> = Vec::new();
-
-        for (index, shift) in shifts.iter().enumerate() {
-            if format!("{shift}{url}").contains(&pat) {
-                res.push(index);
-            }
-        }
-
-        if res.is_empty() {
-            None
-        } else {
-            Some(res)
-        }
-
-        // FIXME: Fix this
-        /*
-        let double = format!("{desc} {desc}", desc = &self.desc);
-
-        let begin: usize;
-        let end: usize;
-
-        dbg!(&double);
-        if let Some(n) = double.find(&pat) {
-            let start = &double[..n];
-            let last = &double[(n + pat_len)..];
-
-            let start_cnt = start.matches(' ').count();
-            let pat_cnt = pat.matches(' ').count();
-
-            begin = if start.ends_with(' ') {
-                start_cnt
-            } else {
-                start_cnt - 1
-            };
-
-            end = if last.starts_with(' ') {
-                begin + pat_cnt
-            } else {
-                begin + pat_cnt + 1
-            };
-
-            dbg!(&start);
-            dbg!(&last);
-            dbg!(&begin);
-            dbg!(&end);
-            return Some((begin..end).collect());
-        }
-        */
+        RapidShifter { desc, url, index }
     }
 
-    /// 通过正则表达式搜索特定字符串
-    /// 参数`all`设置搜索内容是否包括URL
-    #[pyo3(signature = (re, all=false))]
-    pub fn regex_search(&mut self, re: &str, all: bool) -> PyResult<Option<Vec<usize>>> {
-        lazy_check!(self.shifts, self.process());
-
-        let re = match Regex::new(re) {
-            Ok(re) => re,
-            Err(err) => match err {
-                regex::Error::CompiledTooBig(n) => {
-                    return Err(PyRegexCompiledTooBigError::new_err(format!(
-                        "Arg `{re}`(size: {n}) is too big",
-                    )))
-                }
-                regex::Error::Syntax(_) => {
-                    return Err(PyRegexSyntaxError::new_err(format!(
-                        "Arg `{re}` is not a valid Regular Expression",
-                    )))
-                }
-                _ => unreachable!(),
-            },
-        };
-
-        let mut res: Vec<usize> = Vec::new();
-        let shifts = self.shifts.as_ref().unwrap();
-
-        let url = if all {
-            &format!(" {}", self.url.as_ref().map_or(NONE, |s| s.as_str()))
-        } else {
-            ""
-        };
+    /// 描述字符串（不含URL）
+    pub fn desc(&self) -> &str {
+        &self.desc
+    }
 
-        for (index, shift) in shifts.iter().enumerate() {
-            if re.is_match(&format!("{shift}{url}")) {
-                res.push(index);
-            }
-        }
+    /// 匹配到的URL
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
 
-        if res.is_empty() {
-            Ok(None)
-        } else {
-            Ok(Some(res))
-        }
+    /// 移位排序处理函数
+    /// 调用仅会生成所有移位序列并排序，不会返回值
+    /// 需要移位序列参见函数[`RapidShifter::get`]或[`RapidShifter::shifts`]
+    #[inline]
+    pub fn process(&mut self) {
+        self.index.process()
     }
 
     /// 并发生成循环移位序列
     /// 理论上应该更快，但是比Python还慢
-    /// 目前废弃(deprecated)处理
+    /// 目前废弃(deprecated)处理，改为基于标记排序后等同于[`RapidShifter::process`]
     #[deprecated]
-    pub fn qshifts(&mut self, py: Python<'_>) {
-        py.allow_threads(move || {
-            // PERF: Use concurency to "optimize" it
-            // But I find it even slowly than Python ??? why???
-            let words: Vec<String> = self
-                .desc
-                .split_ascii_whitespace()
-                .map(|s| s.to_string())
-                .collect();
-
-            let result: Shift = Arc::new(Mutex::new(Vec::from([self.desc.clone()])));
-
-            let words_clone = words.clone();
-
-            let length = words.len();
-            let left = length / 2;
-            let right = length - left - 1;
-
-            let mut handles = Vec::new();
-
-            let partl = Arc::clone(&result);
-            let partr = Arc::clone(&result);
-
-            handles.push(thread::spawn(move || {
-                //let mut words: Vec<&str> = input.split_ascii_whitespace().collect();
-                //words.rotate_left(length / threads * i);
-                let shifts = &mut RapidShifterIter::new(
-                    words.iter().map(|s| s.as_str()).collect(),
-                    Some(left),
-                    Direction::Left,
-                )
-                .collect();
-
-                partl.lock().unwrap().append(shifts);
-            }));
-
-            handles.push(thread::spawn(move || {
-                //let mut words: Vec<&str> = input.split_ascii_whitespace().collect();
-                //words.rotate_left(length / threads * i);
-                let shifts = &mut RapidShifterIter::new(
-                    words_clone.iter().map(|s| s.as_str()).collect(),
-                    Some(right),
-                    Direction::Right,
-                )
-                .collect();
-
-                partr.lock().unwrap().append(shifts);
-            }));
-
-            /* PERF: The code below is slower...
-
-            let result: Shift = Arc::new(Mutex::new(Vec::from([self.input.clone()])));
-            let mut words: Vec<String> = self
-                .input
-                .split_ascii_whitespace()
-                .map(|s| s.to_string())
-                .collect();
-
-            let length = words.len();
-
-            if threads > length {
-                threads = length;
-            }
-
-            let step = length / threads;
-
-            let mut handles = Vec::new();
-
-            for _ in 0..threads {
-                let part = Arc::clone(&result);
-                //let input = self.input.clone();
-                let words_clone = words.clone();
-
-                handles.push(thread::spawn(move || {
-                    //let mut words: Vec<&str> = input.split_ascii_whitespace().collect();
-                    //words.rotate_left(length / threads * i);
-                    let shifts = &mut RapidShifterIter::new(
-                        words_clone.iter().map(|s| s.as_str()).collect(),
-                        Some(step),
-                        Direction::Left,
-                    )
-                    .collect();
-
-                    part.lock().unwrap().append(shifts);
-                }));
-
-                words.rotate_left(step);
-            }
-
-            let part = Arc::clone(&result);
-            let res = length % threads;
-            words.rotate_left(step);
-
-            let shifts = &mut RapidShifterIter::new(
-                words.iter().map(|s| s.as_str()).collect(),
-                Some(res),
-                Direction::Left,
-            )
-            .collect();
-            part.lock().unwrap().append(shifts);
-            */
-
-            for handle in handles {
-                handle.join().unwrap();
-            }
-
-            let mut result = (*result.lock().unwrap()).to_owned();
-            result.sort_unstable_by(|x, y| magic(x, y));
-            self.shifts = Some(result);
-        })
-    }
-}
-
-impl RapidShifter {
-    fn iter(&self) -> RapidShifterIter {
-        RapidShifterIter::new(
-            self.desc.split_ascii_whitespace().collect(),
-            None,
-            Direction::Left,
-        )
+    pub fn qshifts(&mut self) {
+        self.process()
     }
 }
 
-/// [`RapidShifterLines`]并发设置的最大线程数
-const THREADS: usize = 16;
-
-type UrlID = u64;
-
-/// 接受的行参数类型
-/// 字段`url_id`通过[`RapidShifterLines::urlmap`]对应着一个URL
-#[derive(Clone)]
-struct Item {
-    desc: String,
-    url_id: Option<UrlID>,
-}
-
-impl Item {
-    fn new(desc: String, url: Option<UrlID>) -> Self {
-        Item { desc, url_id: url }
-    }
-}
+shared_methods!(RapidShifter);
 
 /// 多行循环移位结构体`RapidShifterLines`
 ///
-/// 通过哈希([`HashMap<UrlID, String>`])存储URL与每一行移位序列的关系
-/// 通过宏[`lazy_check`]惰性排序
+/// 每一行与其URL存储为一个[`Line`]，移位序列仅存储（行号，单词偏移）标记
+/// 通过宏`lazy_check`惰性排序
 /// 效果同[`RapidShifter`]
 ///
 /// > 注意：本结构体目前不支持`merge`参数，默认行为是合并操作
-#[pyclass]
 pub struct RapidShifterLines {
-    item: Vec<Item>,
-    shifts: Option<Vec<Item>>,
-
-    urlmap: HashMap<UrlID, String>,
+    index: ShiftIndex,
 }
 
-#[pymethods]
 impl RapidShifterLines {
     /// 初始化函数
     /// 参数`item`为给定字符串
     /// 使用正则表达式尝试匹配URL，若匹配失败则设置URL为[`None`]
     /// 仅匹配最后一个单词块（以' '作为分隔符）
-    #[new]
     pub fn new(item: Vec<String>) -> Self {
-        let re = Regex::new(r"^(https?|ftp)://[^\s/$.?#].[^\s]*$").unwrap();
+        let re = Regex::new(URL_RE).unwrap();
 
-        let mut urlmap = HashMap::new();
-        let mut id = 0u64;
-
-        let item = item
+        let lines = item
             .iter()
             .map(|s| {
                 let (desc, url) = s.trim().rsplit_once(' ').unwrap_or_default();
 
                 if re.is_match(url) {
-                    id += 1;
-                    urlmap.insert(id, url.to_string());
-                    return Item::new(desc.to_string(), Some(id));
+                    return Line::new(desc, Some(url.to_string()));
                 };
 
-                Item::new(s.to_owned(), None)
+                Line::new(s, None)
             })
             .collect();
 
         RapidShifterLines {
-            item,
-            shifts: None,
-            urlmap,
+            index: ShiftIndex::new(lines),
         }
     }
 
-    /// 移位排序处理函数
-    ///
-    /// 通过**并发**来加速移位过程，可以同时运行多个移位迭代器
-    /// 调用仅会生成所有移位序列并排序，不会返回值
-    /// 需要移位序列参见函数[`RapidShifterLines::__getitem__`]或[`RapidShifterLines::shifts`]
-    #[inline]
-    pub fn process(&mut self, py: Python<'_>) {
-        // PERF: Use concurency to optimize it
-        // Max threads is set to 16
-        py.allow_threads(move || {
-            let result: Arc<Mutex<Vec<Item>>> = Arc::new(Mutex::new(Vec::new()));
-
-            for pieces in self.item.chunks(THREADS) {
-                let mut handles = Vec::new();
-
-                for piece in pieces {
-                    let string = piece.desc.to_string();
-                    let url_id = piece.url_id;
-
-                    let part = Arc::clone(&result);
-
-                    handles.push(thread::spawn(move || {
-                        let shifts = &mut RapidShifterIter::new(
-                            string.split_ascii_whitespace().collect(),
-                            None,
-                            Direction::Left,
-                        )
-                        .map(|s| Item::new(s, url_id))
-                        .collect();
-                        part.lock().unwrap().append(shifts);
-                    }));
-                }
-
-                for handle in handles {
-                    handle.join().unwrap();
-                }
-            }
-
-            let mut result = (*result.lock().unwrap()).to_owned();
-            result.sort_unstable_by(|x, y| magic(&x.desc, &y.desc));
-            self.shifts = Some(result);
-        })
+    /// 设置并发处理的线程数，`0`表示使用rayon的全局线程池（默认）
+    /// 非零时会创建独立的工作窃取线程池，排序与搜索均在该线程池中进行
+    pub fn with_threads(mut self, threads: usize) -> Result<Self> {
+        self.index.set_threads(threads)?;
+        Ok(self)
     }
 
-    /// 得到特定的循环序列
-    pub fn __getitem__(&mut self, py: Python<'_>, index: usize) -> PyResult<String> {
-        lazy_check!(self.shifts, self.process(py));
-
-        if index >= self.shifts.as_ref().unwrap().len() {
-            return Err(exceptions::PyIndexError::new_err(
-                "arg `index` is out of range",
-            ));
-        }
-
-        let shift = &self.shifts.as_ref().unwrap()[index];
-
-        let url = match shift.url_id {
-            Some(id) => self.urlmap.get(&id).unwrap(),
-            None => NONE,
-        };
-
-        let res = format!("{} {}", &shift.desc, url);
-
-        Ok(res)
+    /// 并发处理使用的线程数
+    pub fn threads(&self) -> usize {
+        self.index.threads()
     }
 
-    /// 得到所有的循环序列
-    pub fn shifts(&mut self, py: Python<'_>) -> Vec<&String> {
-        lazy_check!(self.shifts, self.process(py));
-
-        self.shifts
-            .as_ref()
-            .unwrap()
-            .iter()
-            .map(|s| &s.desc)
-            .collect()
-    }
-
-    /// 展示特定列
-    pub fn show_line(&mut self, py: Python<'_>, line: usize) -> PyResult<()> {
-        lazy_check!(self.shifts, self.process(py));
-
-        if line > self.shifts.as_ref().unwrap().len() {
-            return Err(exceptions::PyIndexError::new_err(
-                "arg `line` is out of range",
-            ));
-        }
-
-        let line = &self.shifts.as_ref().unwrap()[line];
-
-        let desc = &line.desc;
-        let url = match line.url_id {
-            Some(id) => self.urlmap.get(&id).unwrap(),
-            None => NONE,
-        };
-
-        println!("{desc} {url}");
-        Ok(())
-    }
-
-    /// 展示所有列
-    /// 参数`verbose`为是否详细展示
-    #[pyo3(signature = (verbose=false))]
-    pub fn show_all(&mut self, py: Python<'_>, verbose: bool) {
-        lazy_check!(self.shifts, self.process(py));
-
-        let shifts = self.shifts.as_ref().unwrap();
-
-        for (num, line) in shifts.iter().enumerate() {
-            let desc = &line.desc;
-            let url = match line.url_id {
-                Some(id) => self.urlmap.get(&id).unwrap(),
-                None => NONE,
-            };
-
-            if verbose {
-                print!("{}", Color::Purple.color(&format!("[{:0>2}] ", num + 1)));
-                println!("{} {}", Color::Blue.color(desc), Color::Yellow.color(url));
-            } else {
-                println!("{desc} {url}");
-            };
-        }
-    }
-
-    /// 搜索特定字符串
-    /// 参数`all`设置搜索内容是否包括URL
-    #[pyo3(signature = (pat, all=false))]
-    pub fn search(&mut self, py: Python<'_>, pat: String, all: bool) -> Option<Vec<usize>> {
-        // TODO: optimize this
-        //
-        lazy_check!(self.shifts, self.process(py));
-
-        let mut res: Vec<usize> = Vec::new();
-
-        for (index, line) in self.shifts.as_ref().unwrap().iter().enumerate() {
-            let desc = &line.desc;
-            if all {
-                let url = match line.url_id {
-                    Some(id) => self.urlmap.get(&id).unwrap(),
-                    None => NONE,
-                };
-
-                if format!("{} {}", desc, url).contains(&pat) {
-                    res.push(index);
-                }
-            } else if desc.contains(&pat) {
-                res.push(index);
-            }
-        }
-
-        if res.is_empty() {
-            None
-        } else {
-            Some(res)
-        }
-
-        /*
-        let double = format!("{desc} {desc}", desc = &self.desc);
-
-        let begin: usize;
-        let end: usize;
-
-        dbg!(&double);
-        if let Some(n) = double.find(&pat) {
-            let start = &double[..n];
-            let last = &double[(n + pat_len)..];
-
-            let start_cnt = start.matches(' ').count();
-            let pat_cnt = pat.matches(' ').count();
-
-            begin = if start.ends_with(' ') {
-                start_cnt
-            } else {
-                start_cnt - 1
-            };
-
-            end = if last.starts_with(' ') {
-                begin + pat_cnt
-            } else {
-                begin + pat_cnt + 1
-            };
-
-            dbg!(&start);
-            dbg!(&last);
-            dbg!(&begin);
-            dbg!(&end);
-            return Some((begin..end).collect());
-        }
-        */
-
-        // 01234567
-        //
-        // A B C D E F A B C D E F
-        //  "B C D"
-        //   n..(n + len - 1)
-        // "A " =>  1 => 0
-        //  B C D => ban 2 3 4 => 2
-        // " E F ..." 5 ..
-        //
-        // A B C D E F A B C D E F
-        //       " E F A B"
-        // "A B C D" => 3
-        // " E F A B" => none
+    /// 是否已经产生并排序所有移位序列
+    pub fn is_processed(&self) -> bool {
+        self.index.is_processed()
     }
 
-    /// 通过正则表达式搜索特定字符串
-    /// 参数`all`设置搜索内容是否包括URL
-    #[pyo3(signature = (re, all=false))]
-    pub fn regex_search(
-        &mut self,
-        py: Python<'_>,
-        re: &str,
-        all: bool,
-    ) -> PyResult<Option<Vec<usize>>> {
-        lazy_check!(self.shifts, self.process(py));
-
-        let re = match Regex::new(re) {
-            Ok(re) => re,
-            Err(err) => match err {
-                regex::Error::CompiledTooBig(n) => {
-                    return Err(PyRegexCompiledTooBigError::new_err(format!(
-                        "Arg `{re}`(size: {n}) is too big",
-                    )))
-                }
-                regex::Error::Syntax(_) => {
-                    return Err(PyRegexSyntaxError::new_err(format!(
-                        "Arg `{re}` is not a valid Regular Expression",
-                    )))
-                }
-                _ => unreachable!(),
-            },
-        };
-
-        let mut res: Vec<usize> = Vec::new();
-
-        for (index, line) in self.shifts.as_ref().unwrap().iter().enumerate() {
-            let desc = &line.desc;
-            if all {
-                let url = match line.url_id {
-                    Some(id) => self.urlmap.get(&id).unwrap(),
-                    None => NONE,
-                };
-
-                if re.is_match(&format!("{} {}", desc, url)) {
-                    res.push(index);
-                }
-            } else if re.is_match(desc) {
-                res.push(index);
-            }
-        }
-
-        if res.is_empty() {
-            Ok(None)
-        } else {
-            Ok(Some(res))
-        }
+    /// 移位排序处理函数
+    ///
+    /// 仅产生（行号，单词偏移）标记，排序时循环遍历单词比较，不会拼接字符串
+    /// 通过**工作窃取线程池**并发产生标记，每个线程写入各自的缓冲区，再进行并行排序
+    /// 调用仅会生成所有移位序列并排序，不会返回值
+    /// 需要移位序列参见函数[`RapidShifterLines::get`]或[`RapidShifterLines::shifts`]
+    #[inline]
+    pub fn process(&mut self) {
+        self.index.process()
     }
 }
+
+shared_methods!(RapidShifterLines);
@@ -0,0 +1,137 @@
+//! 停用词模块
+//!
+//! 提供停用词表[`StopWords`]，用于过滤以"of"、"the"、"的"等无意义单词开头的移位序列
+//! 停用词仍然保留在其它移位序列的上下文中，仅不再作为移位序列的开头
+//!
+//! 内置英文与中文停用词表，也可以使用自定义单词或从文件读取
+//! 停用词按以空白分隔的单词匹配，中文停用词表只对已用空格分词的中文文本有效
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use crate::error::Result;
+
+/// 内置英文停用词表
+const ENGLISH: &str = include_str!("../data/stopwords_en.txt");
+/// 内置中文停用词表
+const CHINESE: &str = include_str!("../data/stopwords_zh.txt");
+
+/// 停用词表
+///
+/// 匹配时忽略大小写，如"The"同样为停用词
+///
+/// # Example:
+///
+/// ```rust
+/// use rshifter::stopwords::StopWords;
+///
+/// let stop = StopWords::english().with_words(["beijing"]);
+/// assert!(stop.contains("Of"));
+/// assert!(stop.contains("Beijing"));
+/// assert!(!stop.contains("technology"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StopWords {
+    words: HashSet<String>,
+}
+
+impl StopWords {
+    /// 创建空的停用词表
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 内置英文停用词表
+    pub fn english() -> Self {
+        Self::new().with_list(ENGLISH)
+    }
+
+    /// 内置中文停用词表
+    ///
+    /// 停用词须是以空白分隔的完整单词，未分词的中文文本（如"北京的大学"）中不会匹配到"的"，
+    /// 只适用于已用空格分词的输入，如"北京 的 大学"
+    pub fn chinese() -> Self {
+        Self::new().with_list(CHINESE)
+    }
+
+    /// 从文件读取停用词表，格式与[`StopWords::with_list`]相同
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::new().with_list(&fs::read_to_string(path)?))
+    }
+
+    /// 加入若干停用词
+    pub fn with_words<I, S>(mut self, words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.words
+            .extend(words.into_iter().map(|s| s.as_ref().to_lowercase()));
+        self
+    }
+
+    /// 加入停用词列表文本
+    /// 单词以空白分隔，以`#`开头的行为注释
+    pub fn with_list(self, list: &str) -> Self {
+        let words = list
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('#'))
+            .flat_map(str::split_whitespace);
+
+        self.with_words(words)
+    }
+
+    /// 合并另一个停用词表
+    pub fn union(mut self, other: StopWords) -> Self {
+        self.words.extend(other.words);
+        self
+    }
+
+    /// 停用词表是否为空
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// 判断`word`是否为停用词
+    #[inline]
+    pub fn contains(&self, word: &str) -> bool {
+        if self.words.is_empty() {
+            return false;
+        }
+
+        if word.chars().any(char::is_uppercase) {
+            self.words.contains(&word.to_lowercase())
+        } else {
+            self.words.contains(word)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_builtin() {
+        let en = StopWords::english();
+        assert!(en.contains("the") && en.contains("THE") && en.contains("of"));
+        assert!(!en.contains("Beijing"));
+
+        let zh = StopWords::chinese();
+        assert!(zh.contains("的") && zh.contains("我们"));
+        assert!(!zh.contains("北京"));
+
+        assert!(!en.contains("#") && !zh.contains("#"));
+    }
+
+    #[test]
+    fn test_list() {
+        let stop = StopWords::new().with_list("# comment\nfoo bar\n\n  baz\n");
+        assert!(stop.contains("foo") && stop.contains("bar") && stop.contains("Baz"));
+        assert!(!stop.contains("comment"));
+
+        let stop = stop.union(StopWords::chinese());
+        assert!(stop.contains("foo") && stop.contains("的"));
+    }
+}